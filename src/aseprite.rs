@@ -0,0 +1,620 @@
+//! Sprite animations straight from Aseprite's `.aseprite`/`.ase` files, so artists don't need to export sheets.
+//!
+//! Every frame is flattened into its own [`Image`], labelled `frame<index>`, keeping its duration; tags become
+//! named ranges of frames. Only the visible layers are drawn, each blended as `Normal`. Tilemap layers are left
+//! out. Files are hot-reloaded with the `hot_reload` feature like any other asset.
+//!
+//! The format is described in Aseprite's
+//! [file specs](https://github.com/aseprite/aseprite/blob/main/docs/ase-file-specs.md).
+
+use std::io::Read;
+
+use bevy::prelude::*;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext, RenderAssetUsages};
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::character::MovementState;
+
+/// A sprite animation loaded from an Aseprite file.
+#[derive(Asset, TypePath, Clone, Debug, Default)]
+pub struct Aseprite
+{
+    pub frames: Vec<AsepriteFrame>,
+    pub tags: Vec<AsepriteTag>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AsepriteFrame
+{
+    pub image: Handle<Image>,
+    /// How long the frame shows, in seconds.
+    pub duration: f32,
+}
+
+/// A named range of frames, played by an [`AsepriteAnimation`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct AsepriteTag
+{
+    pub name: String,
+    /// First frame of the tag.
+    pub from: usize,
+    /// Last frame of the tag, inclusive.
+    pub to: usize,
+    pub direction: AnimationDirection,
+    /// How many times the tag plays before stopping on its last frame. Zero loops forever.
+    pub repeat: u16,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnimationDirection
+{
+    #[default]
+    Forward,
+    Reverse,
+    /// Forward, then back again.
+    PingPong,
+    /// Backwards, then forward again.
+    PingPongReverse,
+}
+
+impl Aseprite
+{
+    pub fn tag(&self, name: &str) -> Option<&AsepriteTag>
+    {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+}
+
+/// The pixels of an Aseprite file, before they are turned into [`Image`]s.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AsepriteData
+{
+    pub size: UVec2,
+    pub frames: Vec<FrameData>,
+    pub tags: Vec<AsepriteTag>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameData
+{
+    /// Every visible layer flattened, as sRGB RGBA rows from the top down.
+    pub pixels: Vec<u8>,
+    /// In seconds.
+    pub duration: f32,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AsepriteError
+{
+    #[error("could not read aseprite file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("not an aseprite file")]
+    NotAseprite,
+    #[error("aseprite file ends in the middle of a {0}")]
+    Truncated(&'static str),
+    #[error("unsupported color depth of {0} bits")]
+    ColorDepth(u16),
+    #[error("frame {frame} has a cel on layer {layer}, which doesn't exist")]
+    Layer { frame: usize, layer: usize },
+}
+
+const HEADER_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+
+const CHUNK_OLD_PALETTE: u16 = 0x0004;
+const CHUNK_LAYER: u16 = 0x2004;
+const CHUNK_CEL: u16 = 0x2005;
+const CHUNK_TAGS: u16 = 0x2018;
+const CHUNK_PALETTE: u16 = 0x2019;
+
+const LAYER_VISIBLE: u16 = 1;
+const LAYER_BACKGROUND: u16 = 8;
+const LAYER_TYPE_IMAGE: u16 = 0;
+/// Set in the header flags when the layer opacity is meaningful.
+const HEADER_LAYER_OPACITY: u32 = 1;
+
+/// Reads little-endian values off the front of a slice, failing with [`AsepriteError::Truncated`].
+struct Bytes<'a>
+{
+    bytes: &'a [u8],
+    what: &'static str,
+}
+
+impl<'a> Bytes<'a>
+{
+    fn new(bytes: &'a [u8], what: &'static str) -> Self
+    {
+        Self { bytes, what }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], AsepriteError>
+    {
+        if self.bytes.len() < n { return Err(AsepriteError::Truncated(self.what)); }
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn skip(&mut self, n: usize) -> Result<(), AsepriteError>
+    {
+        self.take(n).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, AsepriteError>
+    {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, AsepriteError>
+    {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn i16(&mut self) -> Result<i16, AsepriteError>
+    {
+        Ok(i16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, AsepriteError>
+    {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String, AsepriteError>
+    {
+        let len = self.u16()? as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+
+    fn rest(&mut self) -> &'a [u8]
+    {
+        std::mem::take(&mut self.bytes)
+    }
+}
+
+struct Layer
+{
+    flags: u16,
+    kind: u16,
+    child_level: u16,
+    opacity: u8,
+}
+
+struct Cel
+{
+    layer: usize,
+    position: IVec2,
+    opacity: u8,
+    z_index: i16,
+    content: CelContent,
+}
+
+enum CelContent
+{
+    /// Pixels in the file's color depth.
+    Image { size: UVec2, pixels: Vec<u8> },
+    /// The cel on the same layer in another frame.
+    Linked(usize),
+}
+
+/// Parses an Aseprite file and flattens each of its frames.
+pub fn parse_aseprite(bytes: &[u8]) -> Result<AsepriteData, AsepriteError>
+{
+    let mut header = Bytes::new(bytes, "header");
+    header.skip(4)?;
+    if header.u16()? != HEADER_MAGIC { return Err(AsepriteError::NotAseprite); }
+    let frame_count = header.u16()? as usize;
+    let size = UVec2::new(header.u16()? as u32, header.u16()? as u32);
+    let depth = header.u16()?;
+    if !matches!(depth, 8 | 16 | 32) { return Err(AsepriteError::ColorDepth(depth)); }
+    let flags = header.u32()?;
+    header.skip(2 + 4 + 4)?;
+    let transparent_index = header.u8()?;
+    header.skip(128 - 29)?;
+
+    let mut body = Bytes::new(header.rest(), "frame");
+    let mut layers = Vec::new();
+    let mut palette = vec![[0; 4]; 256];
+    let mut has_new_palette = false;
+    let mut tags = Vec::new();
+    let mut frames = Vec::with_capacity(frame_count);
+
+    for _ in 0..frame_count
+    {
+        let frame_len = body.u32()? as usize;
+        let mut frame = Bytes::new(body.take(frame_len.saturating_sub(4))?, "frame");
+        if frame.u16()? != FRAME_MAGIC { return Err(AsepriteError::NotAseprite); }
+        let old_chunk_count = frame.u16()? as usize;
+        let duration = frame.u16()? as f32 / 1000.0;
+        frame.skip(2)?;
+        let chunk_count = match frame.u32()? as usize { 0 => old_chunk_count, n => n };
+
+        let mut cels = Vec::new();
+        for _ in 0..chunk_count
+        {
+            let chunk_len = frame.u32()? as usize;
+            let chunk_type = frame.u16()?;
+            let mut chunk = Bytes::new(frame.take(chunk_len.saturating_sub(6))?, "chunk");
+            match chunk_type
+            {
+                CHUNK_LAYER =>
+                {
+                    let flags = chunk.u16()?;
+                    let kind = chunk.u16()?;
+                    let child_level = chunk.u16()?;
+                    chunk.skip(6)?;
+                    let opacity = chunk.u8()?;
+                    layers.push(Layer { flags, kind, child_level, opacity });
+                }
+                CHUNK_CEL =>
+                {
+                    let layer = chunk.u16()? as usize;
+                    let position = IVec2::new(chunk.i16()? as i32, chunk.i16()? as i32);
+                    let opacity = chunk.u8()?;
+                    let cel_type = chunk.u16()?;
+                    let z_index = chunk.i16()?;
+                    chunk.skip(5)?;
+                    let content = match cel_type
+                    {
+                        0 | 2 =>
+                        {
+                            let size = UVec2::new(chunk.u16()? as u32, chunk.u16()? as u32);
+                            let pixels = if cel_type == 0
+                            {
+                                chunk.rest().to_vec()
+                            }
+                            else
+                            {
+                                let mut pixels = Vec::new();
+                                flate2::read::ZlibDecoder::new(chunk.rest()).read_to_end(&mut pixels)?;
+                                pixels
+                            };
+                            CelContent::Image { size, pixels }
+                        }
+                        1 => CelContent::Linked(chunk.u16()? as usize),
+                        // Tilemaps
+                        _ => continue,
+                    };
+                    cels.push(Cel { layer, position, opacity, z_index, content });
+                }
+                CHUNK_PALETTE =>
+                {
+                    let new_size = chunk.u32()? as usize;
+                    let first = chunk.u32()? as usize;
+                    let last = chunk.u32()? as usize;
+                    chunk.skip(8)?;
+                    palette.resize(new_size.max(palette.len()), [0; 4]);
+                    for index in first..=last
+                    {
+                        let entry_flags = chunk.u16()?;
+                        let color = chunk.take(4)?;
+                        if entry_flags & 1 != 0 { chunk.string()?; }
+                        if let Some(entry) = palette.get_mut(index) { entry.copy_from_slice(color); }
+                    }
+                    has_new_palette = true;
+                }
+                CHUNK_OLD_PALETTE if !has_new_palette =>
+                {
+                    let mut index = 0;
+                    for _ in 0..chunk.u16()?
+                    {
+                        index += chunk.u8()? as usize;
+                        let count = match chunk.u8()? { 0 => 256, n => n as usize };
+                        for _ in 0..count
+                        {
+                            let rgb = chunk.take(3)?;
+                            if let Some(entry) = palette.get_mut(index) { *entry = [rgb[0], rgb[1], rgb[2], 255]; }
+                            index += 1;
+                        }
+                    }
+                }
+                CHUNK_TAGS =>
+                {
+                    let count = chunk.u16()?;
+                    chunk.skip(8)?;
+                    for _ in 0..count
+                    {
+                        let from = chunk.u16()? as usize;
+                        let to = chunk.u16()? as usize;
+                        let direction = match chunk.u8()?
+                        {
+                            1 => AnimationDirection::Reverse,
+                            2 => AnimationDirection::PingPong,
+                            3 => AnimationDirection::PingPongReverse,
+                            _ => AnimationDirection::Forward,
+                        };
+                        let repeat = chunk.u16()?;
+                        chunk.skip(6 + 3 + 1)?;
+                        let name = chunk.string()?;
+                        tags.push(AsepriteTag { name, from, to, direction, repeat });
+                    }
+                }
+                _ => {}
+            }
+        }
+        frames.push((cels, duration));
+    }
+
+    let visible = visible_layers(&layers);
+    let mut flattened = Vec::with_capacity(frames.len());
+    for (frame_index, (cels, duration)) in frames.iter().enumerate()
+    {
+        let mut order: Vec<&Cel> = cels.iter().collect();
+        // The order Aseprite draws in: by layer, moved up or down by the z-index, which wins ties.
+        order.sort_by_key(|cel| (cel.layer as i32 + cel.z_index as i32, cel.z_index));
+
+        let mut pixels = vec![0; (size.x * size.y * 4) as usize];
+        for cel in order
+        {
+            let layer = layers.get(cel.layer).ok_or(AsepriteError::Layer { frame: frame_index, layer: cel.layer })?;
+            if !visible[cel.layer] || layer.kind != LAYER_TYPE_IMAGE { continue; }
+
+            let content = match &cel.content
+            {
+                CelContent::Linked(linked) => frames.get(*linked)
+                    .and_then(|(cels, _)| cels.iter().find(|other| other.layer == cel.layer))
+                    .map(|other| &other.content),
+                content => Some(content),
+            };
+            let Some(CelContent::Image { size: cel_size, pixels: cel_pixels }) = content else { continue; };
+
+            let layer_opacity = if flags & HEADER_LAYER_OPACITY != 0 { layer.opacity } else { 255 };
+            let opacity = cel.opacity as f32 / 255.0 * layer_opacity as f32 / 255.0;
+            let transparent = (layer.flags & LAYER_BACKGROUND == 0).then_some(transparent_index);
+            let bytes_per_pixel = depth as usize / 8;
+
+            for y in 0..cel_size.y as i32
+            {
+                for x in 0..cel_size.x as i32
+                {
+                    let target = cel.position + IVec2::new(x, y);
+                    if target.cmplt(IVec2::ZERO).any() || target.cmpge(size.as_ivec2()).any() { continue; }
+
+                    let source = (y as usize * cel_size.x as usize + x as usize) * bytes_per_pixel;
+                    let Some(source) = cel_pixels.get(source..source + bytes_per_pixel) else { continue; };
+                    let color = match depth
+                    {
+                        32 => [source[0], source[1], source[2], source[3]],
+                        16 => [source[0], source[0], source[0], source[1]],
+                        _ if Some(source[0]) == transparent => [0; 4],
+                        _ => palette.get(source[0] as usize).copied().unwrap_or([0; 4]),
+                    };
+
+                    let target = (target.y as usize * size.x as usize + target.x as usize) * 4;
+                    blend(&mut pixels[target..target + 4], color, opacity);
+                }
+            }
+        }
+        flattened.push(FrameData { pixels, duration: *duration });
+    }
+
+    Ok(AsepriteData { size, frames: flattened, tags })
+}
+
+/// Whether each layer is shown: visible itself, and in no hidden group.
+fn visible_layers(layers: &[Layer]) -> Vec<bool>
+{
+    // Whether the last layer seen at each child level is shown, for the layers nested in it.
+    let mut shown_at_level: Vec<bool> = Vec::new();
+    layers.iter()
+        .map(|layer| {
+            let level = layer.child_level as usize;
+            shown_at_level.truncate(level);
+            let parent_shown = level == 0 || shown_at_level.get(level - 1).copied().unwrap_or(true);
+            let shown = parent_shown && layer.flags & LAYER_VISIBLE != 0;
+            shown_at_level.resize(level, true);
+            shown_at_level.push(shown);
+            shown
+        })
+        .collect()
+}
+
+/// Draws `color` over `target`, both straight RGBA.
+fn blend(target: &mut [u8], color: [u8; 4], opacity: f32)
+{
+    let source_alpha = color[3] as f32 / 255.0 * opacity;
+    if source_alpha <= 0.0 { return; }
+    let target_alpha = target[3] as f32 / 255.0;
+    let alpha = source_alpha + target_alpha * (1.0 - source_alpha);
+    for (target, source) in target[..3].iter_mut().zip(&color[..3])
+    {
+        let mixed = *source as f32 * source_alpha + *target as f32 * target_alpha * (1.0 - source_alpha);
+        *target = (mixed / alpha).round() as u8;
+    }
+    target[3] = (alpha * 255.0).round() as u8;
+}
+
+/// Whether `path` is loaded by the [`AsepriteLoader`].
+pub fn is_aseprite(path: &str) -> bool
+{
+    path.ends_with(".aseprite") || path.ends_with(".ase")
+}
+
+#[derive(Default)]
+pub struct AsepriteLoader;
+
+impl AssetLoader for AsepriteLoader
+{
+    type Asset = Aseprite;
+    type Settings = ();
+    type Error = AsepriteError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error>
+    {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let data = parse_aseprite(&bytes)?;
+
+        let frames = data.frames.into_iter()
+            .enumerate()
+            .map(|(index, frame)| {
+                let image = Image::new(
+                    Extent3d { width: data.size.x, height: data.size.y, ..default() },
+                    TextureDimension::D2,
+                    frame.pixels,
+                    TextureFormat::Rgba8UnormSrgb,
+                    RenderAssetUsages::RENDER_WORLD,
+                );
+                AsepriteFrame {
+                    image: load_context.add_labeled_asset(format!("frame{index}"), image),
+                    duration: frame.duration,
+                }
+            })
+            .collect();
+        Ok(Aseprite { frames, tags: data.tags })
+    }
+
+    fn extensions(&self) -> &[&str]
+    {
+        &["aseprite", "ase"]
+    }
+}
+
+/// Plays an [`Aseprite`] on the entity's [`Sprite`], one tag at a time.
+#[derive(Component, Clone, Debug, Default)]
+#[require(Sprite)]
+pub struct AsepriteAnimation
+{
+    pub aseprite: Handle<Aseprite>,
+    /// The tag playing, or every frame in order when `None` or not in the file.
+    tag: Option<String>,
+    /// The frame showing, `None` until the animation starts.
+    frame: Option<usize>,
+    /// Seconds the frame has been showing.
+    elapsed: f32,
+    /// Whether the animation is going backwards through the tag.
+    backwards: bool,
+    /// How many times the tag has played through.
+    plays: u16,
+}
+
+impl AsepriteAnimation
+{
+    pub fn new(aseprite: Handle<Aseprite>) -> Self
+    {
+        Self { aseprite, ..default() }
+    }
+
+    pub fn tag(&self) -> Option<&str>
+    {
+        self.tag.as_deref()
+    }
+
+    pub fn frame(&self) -> Option<usize>
+    {
+        self.frame
+    }
+
+    /// Starts playing `tag` from the beginning, unless it's already playing.
+    pub fn play(&mut self, tag: &str)
+    {
+        if self.tag.as_deref() == Some(tag) { return; }
+        self.tag = Some(tag.to_owned());
+        self.frame = None;
+    }
+
+    /// Moves the animation on by `dt` seconds.
+    pub fn advance(&mut self, aseprite: &Aseprite, dt: f32)
+    {
+        let Some(last) = aseprite.frames.len().checked_sub(1) else { return; };
+        let (from, to, direction, repeat) = match self.tag.as_deref().and_then(|tag| aseprite.tag(tag))
+        {
+            Some(tag) => (tag.from.min(last), tag.to.min(last), tag.direction, tag.repeat),
+            None => (0, last, AnimationDirection::Forward, 0),
+        };
+        let starts_backwards = matches!(direction, AnimationDirection::Reverse | AnimationDirection::PingPongReverse);
+        let ping_pong = matches!(direction, AnimationDirection::PingPong | AnimationDirection::PingPongReverse);
+
+        let mut frame = match self.frame
+        {
+            Some(frame) if (from..=to).contains(&frame) => frame,
+            // Just started, or the file changed under it.
+            _ =>
+            {
+                self.elapsed = 0.0;
+                self.plays = 0;
+                self.backwards = starts_backwards;
+                self.frame = Some(if starts_backwards { to } else { from });
+                return;
+            }
+        };
+
+        self.elapsed += dt;
+        loop
+        {
+            let duration = aseprite.frames[frame].duration;
+            if duration <= 0.0 || self.elapsed < duration { break; }
+
+            let at_end = if self.backwards { frame == from } else { frame == to };
+            if at_end
+            {
+                // A ping-pong plays through once it's there and back again.
+                if !ping_pong || from == to || self.backwards != starts_backwards
+                {
+                    self.plays = self.plays.saturating_add(1);
+                    if repeat != 0 && self.plays >= repeat
+                    {
+                        self.elapsed = 0.0;
+                        break;
+                    }
+                }
+                if ping_pong { self.backwards = !self.backwards; }
+            }
+
+            self.elapsed -= duration;
+            frame = if from == to
+            {
+                from
+            }
+            else if at_end && !ping_pong
+            {
+                if self.backwards { to } else { from }
+            }
+            else if self.backwards
+            {
+                frame - 1
+            }
+            else
+            {
+                frame + 1
+            };
+        }
+        self.frame = Some(frame);
+    }
+}
+
+/// Plays the tag of each character's [`Pose`](crate::character::Pose) on its [`AsepriteAnimation`].
+pub(crate) fn animate_poses(mut characters: Query<(&MovementState, &mut AsepriteAnimation)>)
+{
+    for (state, mut animation) in characters.iter_mut()
+    {
+        animation.play(state.pose.tag());
+    }
+}
+
+/// Moves every [`AsepriteAnimation`] on and shows its frame on the [`Sprite`].
+pub(crate) fn animate_sprites(
+    time: Res<Time>,
+    aseprites: Res<Assets<Aseprite>>,
+    mut animations: Query<(&mut AsepriteAnimation, &mut Sprite)>,
+)
+{
+    for (mut animation, mut sprite) in animations.iter_mut()
+    {
+        let Some(aseprite) = aseprites.get(&animation.aseprite) else { continue; };
+        animation.advance(aseprite, time.delta_secs());
+
+        let Some(frame) = animation.frame.and_then(|frame| aseprite.frames.get(frame)) else { continue; };
+        if sprite.image != frame.image
+        {
+            sprite.image = frame.image.clone();
+        }
+    }
+}
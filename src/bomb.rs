@@ -0,0 +1,360 @@
+use std::borrow::Cow;
+
+use bevy::prelude::*;
+
+use bevy::ecs::entity::EntityHashSet;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::bomb_type::{BombType, BombTypes, StandardBomb};
+use crate::character::{MovementState, Player};
+use crate::input::Action;
+use crate::inventory::BombInventory;
+use crate::physics::{collision_groups, GROUP_BOMB, GROUP_PROXIMITY_PLACE_SPOT, GROUP_PROXIMITY_PLACER};
+use crate::sensor::{SensorBundle, SensorContact, SensorInteraction};
+use crate::sticky::Sticky;
+
+#[derive(Bundle, Clone, Debug)]
+pub struct BombPlaceSpotBundle
+{
+    bomb_place_spot: BombPlaceSpot,
+    overlaps: ProximityOverlaps,
+    sensor_bundle: SensorBundle,
+}
+
+impl BombPlaceSpotBundle
+{
+    const MEMBERSHIPS:  Group = GROUP_PROXIMITY_PLACE_SPOT;
+
+    pub fn ball_with_radius(radius: f32) -> Self
+    {
+        Self {
+            bomb_place_spot: BombPlaceSpot,
+            overlaps: ProximityOverlaps::default(),
+            sensor_bundle: SensorBundle {
+                sensor: Sensor,
+                collider: Collider::ball(radius),
+                active_events: ActiveEvents::COLLISION_EVENTS,
+                collision_groups: collision_groups(Self::MEMBERSHIPS),
+                transform: Transform::default(),
+                visibility: Visibility::Inherited,
+                active_collision_types: ActiveCollisionTypes::all(),
+            },
+        }
+    }
+}
+
+#[derive(Bundle, Clone, Debug)]
+pub struct BombPlacerBundle
+{
+    bomb_proximity_placer: BombPromixityPlacer,
+    overlaps: ProximityOverlaps,
+    selected_spot: SelectedPlaceSpot,
+    sensor_bundle: SensorBundle,
+} 
+
+impl BombPlacerBundle
+{
+    const MEMBERSHIPS:  Group = GROUP_PROXIMITY_PLACER;
+
+    pub fn ball_with_radius(radius: f32) -> Self
+    {
+        Self {
+            bomb_proximity_placer: BombPromixityPlacer,
+            overlaps: ProximityOverlaps::default(),
+            selected_spot: SelectedPlaceSpot::default(),
+            sensor_bundle: SensorBundle {
+                sensor: Sensor,
+                collider: Collider::ball(radius),
+                active_events: ActiveEvents::COLLISION_EVENTS,
+                collision_groups: collision_groups(Self::MEMBERSHIPS),
+                transform: Transform::default(),
+                visibility: Visibility::Inherited,
+                active_collision_types: ActiveCollisionTypes::all(),
+            },
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct BombPlaceSpot;
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct BombPromixityPlacer;
+
+/// Proximity sensors currently overlapping this one.
+/// Kept on both [`BombPromixityPlacer`]s and [`BombPlaceSpot`]s, holding the other kind.
+#[derive(Component, Clone, Debug, Default)]
+pub struct ProximityOverlaps(pub EntityHashSet);
+
+/// The [`BombPlaceSpot`] a [`BombPromixityPlacer`] would place a bomb on: the nearest one it overlaps.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SelectedPlaceSpot(pub Option<Entity>);
+
+/// Hidden sprite under a [`BombPlaceSpot`], shown while a placer is in range.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct BombPreview;
+
+/// A live bomb, behaving according to the [`BombType`] of its [`BombKind`].
+/// Placed bombs are parented to the [`BombPlaceSpot`] they were placed on,
+/// thrown ones are [`Sticky`] until they are [`StuckTo`](crate::sticky::StuckTo) something.
+#[derive(Component, Clone, Copy, Debug, Default)]
+#[require(BombKind)]
+pub struct Bomb;
+
+/// Which kind of [`Bomb`] this is, or a [`BombInventory`] holds, by name.
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct BombKind(pub Cow<'static, str>);
+
+impl BombKind
+{
+    pub const STANDARD: BombKind = BombKind(Cow::Borrowed("standard"));
+    pub const CLUSTER: BombKind = BombKind(Cow::Borrowed("cluster"));
+    /// What a [`CLUSTER`](Self::CLUSTER) bomb bursts into.
+    pub const CLUSTER_SHARD: BombKind = BombKind(Cow::Borrowed("cluster_shard"));
+    pub const MINE: BombKind = BombKind(Cow::Borrowed("mine"));
+    pub const GRAVITY_WELL: BombKind = BombKind(Cow::Borrowed("gravity_well"));
+    pub const FREEZE: BombKind = BombKind(Cow::Borrowed("freeze"));
+}
+
+impl Default for BombKind
+{
+    fn default() -> Self
+    {
+        Self::STANDARD
+    }
+}
+
+/// The player who placed or threw a [`Bomb`], and can detonate it remotely.
+#[derive(Component, Clone, Copy, Debug)]
+#[relationship(relationship_target = OwnedBombs)]
+pub struct BombOwner(pub Entity);
+
+/// The live bombs a player owns, oldest first.
+#[derive(Component, Clone, Debug, Default)]
+#[relationship_target(relationship = BombOwner)]
+pub struct OwnedBombs(Vec<Entity>);
+
+/// Time until a [`Bomb`] detonates.
+#[derive(Component, Clone, Debug)]
+pub struct Fuse(pub Timer);
+
+/// Written when a [`Bomb`]'s [`Fuse`] runs out, just before the bomb is despawned.
+#[derive(Event, Clone, Debug)]
+pub struct BombDetonated
+{
+    pub bomb: Entity,
+    pub kind: BombKind,
+    pub position: Vec2,
+}
+
+pub(crate) fn track_proximity_overlaps(
+    mut contacts: EventReader<SensorContact<BombPromixityPlacer, BombPlaceSpot>>,
+    mut overlaps: Query<&mut ProximityOverlaps>,
+)
+{
+    for contact in contacts.read()
+    {
+        for (this, other) in [(contact.a, contact.b), (contact.b, contact.a)]
+        {
+            let Ok(mut overlaps) = overlaps.get_mut(this) else { continue; };
+            match contact.interaction
+            {
+                SensorInteraction::Entered => { overlaps.0.insert(other); },
+                SensorInteraction::Exited => { overlaps.0.remove(&other); },
+            }
+        }
+    }
+}
+
+/// Resolves each placer's [`SelectedPlaceSpot`] to the nearest spot it overlaps.
+/// Ties are broken by entity so the choice doesn't depend on hash order.
+pub(crate) fn select_place_spots(
+    mut placers: Query<(&ProximityOverlaps, &GlobalTransform, &mut SelectedPlaceSpot), With<BombPromixityPlacer>>,
+    spots: Query<&GlobalTransform, With<BombPlaceSpot>>,
+)
+{
+    for (overlaps, transform, mut selected) in placers.iter_mut()
+    {
+        let origin = transform.translation().xy();
+
+        let nearest = overlaps.0.iter()
+            .filter_map(|&spot| {
+                let distance = spots.get(spot).ok()?.translation().xy().distance_squared(origin);
+                Some((spot, distance))
+            })
+            .min_by(|(a, a_dist), (b, b_dist)| a_dist.total_cmp(b_dist).then(a.cmp(b)))
+            .map(|(spot, _)| spot);
+
+        selected.set_if_neq(SelectedPlaceSpot(nearest));
+    }
+}
+
+/// Shows the [`BombPreview`] of every spot selected by some placer and hides the rest.
+pub(crate) fn update_bomb_previews(
+    placers: Query<&SelectedPlaceSpot>,
+    mut previews: Query<(&ChildOf, &mut Visibility), With<BombPreview>>,
+)
+{
+    for (child_of, mut visibility) in previews.iter_mut()
+    {
+        let selected = placers.iter().any(|&SelectedPlaceSpot(spot)| spot == Some(child_of.parent()));
+        visibility.set_if_neq(if selected { Visibility::Inherited } else { Visibility::Hidden });
+    }
+}
+
+/// Places a [`Bomb`] on the [`SelectedPlaceSpot`] of each placer on [`Action::PlaceBomb`],
+/// taken from the [`BombInventory`] of the player carrying the placer.
+/// A spot holds at most one live bomb at a time.
+pub(crate) fn place_bombs(
+    mut commands: Commands,
+    actions: Res<ButtonInput<Action>>,
+    placers: Query<(&SelectedPlaceSpot, &ChildOf), With<BombPromixityPlacer>>,
+    mut inventories: Query<&mut BombInventory>,
+    mut states: Query<&mut MovementState>,
+    spots: Query<Option<&Children>, With<BombPlaceSpot>>,
+    bombs: Query<(), With<Bomb>>,
+    types: Res<BombTypes>,
+)
+{
+    if !actions.just_pressed(Action::PlaceBomb) { return; }
+
+    for (&SelectedPlaceSpot(target), child_of) in placers.iter()
+    {
+        let Some(spot) = target else { continue; };
+        let Ok(spot_c) = spots.get(spot) else { continue; };
+
+        let occupied = spot_c.is_some_and(|c| c.iter().any(|child| bombs.contains(child)));
+        if occupied { continue; }
+
+        let owner = child_of.parent();
+        let Some(kind) = take_bomb(&mut inventories, owner) else { continue; };
+        let Some(bomb_type) = types.get_or_warn(&kind) else { continue; };
+
+        debug!("Placed {:?} bomb on spot {:?}", kind.0, spot);
+        if let Ok(mut state) = states.get_mut(owner) { state.place_bomb(); }
+        commands.entity(spot).with_child((
+            armed_bomb(kind, bomb_type),
+            BombOwner(owner),
+            Transform::from_xyz(0.0, 0.0, 1.0),
+        ));
+    }
+}
+
+/// The next bomb `owner` places or throws: its selected kind, if its [`BombInventory`] has any left.
+/// Owners without an inventory have endless standard bombs.
+pub(crate) fn take_bomb(inventories: &mut Query<&mut BombInventory>, owner: Entity) -> Option<BombKind>
+{
+    match inventories.get_mut(owner)
+    {
+        Ok(mut inventory) =>
+        {
+            let kind = inventory.take_selected();
+            if kind.is_none() { debug!("{:?} is out of bombs", owner); }
+            kind
+        }
+        Err(_) => Some(BombKind::STANDARD),
+    }
+}
+
+pub const BOMB_RADIUS: f32 = 4.0;
+
+/// What every live [`Bomb`] of `kind` has, placed or thrown: its fuse lit and its look.
+pub fn armed_bomb(kind: BombKind, bomb_type: &dyn BombType) -> impl Bundle + use<>
+{
+    (
+        Bomb,
+        kind,
+        Fuse(Timer::from_seconds(bomb_type.fuse_secs(), TimerMode::Once)),
+        Sprite::from_color(bomb_type.color(), [8.0, 8.0].into()),
+    )
+}
+
+/// The body of a bomb flying from `position` at `velocity`, sticking to the first thing it hits.
+pub fn flying_bomb(position: Vec2, velocity: Vec2) -> impl Bundle
+{
+    (
+        Sticky,
+        RigidBody::Dynamic,
+        Collider::ball(BOMB_RADIUS),
+        collision_groups(GROUP_BOMB),
+        ActiveEvents::COLLISION_EVENTS,
+        Velocity::linear(velocity),
+        Transform::from_translation(position.extend(1.0)),
+    )
+}
+
+/// A lit standard [`Bomb`] flying from `position` at `velocity`.
+pub fn thrown_bomb(position: Vec2, velocity: Vec2) -> impl Bundle
+{
+    (armed_bomb(BombKind::STANDARD, &StandardBomb::default()), flying_bomb(position, velocity))
+}
+
+/// Remote detonation: burns down the [`Fuse`] of every bomb a player owns on [`Action::Detonate`],
+/// or of just their oldest one on [`Action::DetonateOldest`].
+///
+/// Like moving and throwing, this assumes a single player: there's one [`ButtonInput<Action>`], not one per
+/// [`Player`], so with several players one press sets off the bombs of each of them.
+pub(crate) fn detonate_bombs(
+    actions: Res<ButtonInput<Action>>,
+    owners: Query<&OwnedBombs, With<Player>>,
+    mut fuses: Query<&mut Fuse, With<Bomb>>,
+)
+{
+    let all = actions.just_pressed(Action::Detonate);
+    if !all && !actions.just_pressed(Action::DetonateOldest) { return; }
+
+    for owned in owners.iter()
+    {
+        let count = if all { owned.len() } else { 1 };
+        for bomb in owned.iter().take(count)
+        {
+            let Ok(mut fuse) = fuses.get_mut(bomb) else { continue; };
+            let duration = fuse.0.duration();
+            fuse.0.set_elapsed(duration);
+        }
+    }
+}
+
+/// Counts down every [`Fuse`] and detonates the bombs whose fuse ran out.
+/// Bombs whose type [arms on impact](BombType::arms_on_impact) don't count down while still flying.
+pub(crate) fn tick_bomb_fuses(
+    mut commands: Commands,
+    mut bombs: Query<(Entity, &BombKind, &mut Fuse, &GlobalTransform, Has<Sticky>), With<Bomb>>,
+    mut detonations: EventWriter<BombDetonated>,
+    types: Res<BombTypes>,
+    time: Res<Time>,
+)
+{
+    for (bomb, kind, mut fuse, transform, flying) in bombs.iter_mut()
+    {
+        if flying && types.get(kind).is_some_and(|bomb_type| bomb_type.arms_on_impact()) { continue; }
+        if !fuse.0.tick(time.delta()).finished() { continue; }
+
+        detonate(&mut commands, &mut detonations, bomb, kind, transform);
+    }
+}
+
+/// Writes the [`BombDetonated`] of `bomb` and despawns it.
+pub(crate) fn detonate(commands: &mut Commands, detonations: &mut EventWriter<BombDetonated>, bomb: Entity, kind: &BombKind, transform: &GlobalTransform)
+{
+    let position = transform.translation().xy();
+    debug!("{:?} bomb {:?} detonated at {:?}", kind.0, bomb, position);
+    detonations.write(BombDetonated { bomb, kind: kind.clone(), position });
+    commands.entity(bomb).despawn();
+}
+
+/// Sets off the effect of each detonated bomb's [`BombType`].
+pub(crate) fn set_off_detonations(
+    mut commands: Commands,
+    mut detonations: EventReader<BombDetonated>,
+    types: Res<BombTypes>,
+)
+{
+    for detonation in detonations.read()
+    {
+        let Some(bomb_type) = types.get(&detonation.kind) else { continue; };
+        bomb_type.detonate(detonation, &types, &mut commands);
+    }
+}
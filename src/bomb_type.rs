@@ -0,0 +1,365 @@
+//! The kinds of [`Bomb`](crate::bomb::Bomb) there are, and what each does when it detonates.
+//!
+//! Every [`BombKind`] names a [`BombType`] in the [`BombTypes`] registry. New kinds are added by
+//! implementing [`BombType`] and registering it with [`BombTypeAppExt::register_bomb_type`].
+
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use bevy::color::palettes::css as css_colors;
+use bevy::platform::collections::HashMap;
+use bevy_rapier2d::prelude::*;
+
+use crate::bomb::{armed_bomb, flying_bomb, BombDetonated, BombKind, BOMB_RADIUS};
+use crate::explosion::Explosion;
+
+/// How a kind of bomb looks and behaves.
+pub trait BombType: Send + Sync + 'static
+{
+    /// Seconds from being placed or thrown until it detonates.
+    fn fuse_secs(&self) -> f32;
+    /// How far its effect reaches, in pixels.
+    fn radius(&self) -> f32;
+    fn color(&self) -> Color;
+    /// Whether the fuse of a thrown bomb only starts burning once it has stuck to something.
+    fn arms_on_impact(&self) -> bool { false }
+    /// Sets off its effect where it detonated.
+    fn detonate(&self, detonation: &BombDetonated, types: &BombTypes, commands: &mut Commands);
+}
+
+/// Every [`BombType`], by [`BombKind`].
+#[derive(Resource, Default)]
+pub struct BombTypes(HashMap<BombKind, Box<dyn BombType>>);
+
+impl BombTypes
+{
+    /// Makes `kind` behave like `bomb_type`, replacing whatever it was before.
+    pub fn register(&mut self, kind: BombKind, bomb_type: impl BombType)
+    {
+        self.0.insert(kind, Box::new(bomb_type));
+    }
+
+    pub fn get(&self, kind: &BombKind) -> Option<&dyn BombType>
+    {
+        self.0.get(kind).map(|bomb_type| &**bomb_type)
+    }
+
+    /// Like [`get`](Self::get), complaining about kinds that were never registered.
+    pub fn get_or_warn(&self, kind: &BombKind) -> Option<&dyn BombType>
+    {
+        let bomb_type = self.get(kind);
+        if bomb_type.is_none() { warn!("Unknown bomb kind {:?}", kind.0); }
+        bomb_type
+    }
+}
+
+pub trait BombTypeAppExt
+{
+    /// Registers `bomb_type` in the [`BombTypes`] as `kind`.
+    fn register_bomb_type(&mut self, kind: BombKind, bomb_type: impl BombType) -> &mut Self;
+}
+
+impl BombTypeAppExt for App
+{
+    fn register_bomb_type(&mut self, kind: BombKind, bomb_type: impl BombType) -> &mut Self
+    {
+        self.init_resource::<BombTypes>();
+        self.world_mut().resource_mut::<BombTypes>().register(kind, bomb_type);
+        self
+    }
+}
+
+/// Blows up in an [`Explosion`], pushing everything around away.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StandardBomb
+{
+    pub fuse_secs: f32,
+    pub radius: f32,
+    pub impulse: f32,
+    pub knockback: f32,
+    /// Radius of the hole it blows in destructible terrain.
+    pub carve_radius: f32,
+    pub color: Srgba,
+}
+
+impl Default for StandardBomb
+{
+    fn default() -> Self
+    {
+        Self { fuse_secs: 3.0, radius: 64.0, impulse: 120_000.0, knockback: 240.0, carve_radius: 16.0, color: css_colors::ORANGE_RED }
+    }
+}
+
+impl BombType for StandardBomb
+{
+    fn fuse_secs(&self) -> f32 { self.fuse_secs }
+    fn radius(&self) -> f32 { self.radius }
+    fn color(&self) -> Color { self.color.into() }
+
+    fn detonate(&self, detonation: &BombDetonated, _types: &BombTypes, commands: &mut Commands)
+    {
+        commands.send_event(Explosion {
+            carve_radius: self.carve_radius,
+            ..Explosion::new(detonation.position, self.radius, self.impulse, self.knockback)
+        });
+    }
+}
+
+/// A small blast that flings `shards` more bombs of kind `shard` out in a fan.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClusterBomb
+{
+    pub blast: StandardBomb,
+    pub shard: BombKind,
+    pub shards: u32,
+    pub shard_speed: f32,
+}
+
+impl Default for ClusterBomb
+{
+    fn default() -> Self
+    {
+        Self {
+            blast: StandardBomb { radius: 40.0, impulse: 60_000.0, knockback: 160.0, carve_radius: 10.0, color: css_colors::ORANGE, ..default() },
+            shard: BombKind::CLUSTER_SHARD,
+            shards: 5,
+            shard_speed: 120.0,
+        }
+    }
+}
+
+impl ClusterBomb
+{
+    /// What its shards are: quick little standard bombs.
+    pub fn default_shard() -> StandardBomb
+    {
+        StandardBomb { fuse_secs: 1.0, radius: 32.0, impulse: 40_000.0, knockback: 120.0, carve_radius: 6.0, color: css_colors::YELLOW }
+    }
+}
+
+impl BombType for ClusterBomb
+{
+    fn fuse_secs(&self) -> f32 { self.blast.fuse_secs }
+    fn radius(&self) -> f32 { self.blast.radius }
+    fn color(&self) -> Color { self.blast.color.into() }
+
+    fn detonate(&self, detonation: &BombDetonated, types: &BombTypes, commands: &mut Commands)
+    {
+        self.blast.detonate(detonation, types, commands);
+
+        let Some(shard_type) = types.get_or_warn(&self.shard) else { return; };
+        // Evenly spread over the upper half circle, none straight sideways.
+        for i in 0..self.shards
+        {
+            let direction = Vec2::from_angle(PI * (i + 1) as f32 / (self.shards + 1) as f32);
+            let position = detonation.position + direction * BOMB_RADIUS * 2.0;
+            commands.spawn((armed_bomb(self.shard.clone(), shard_type), flying_bomb(position, direction * self.shard_speed)));
+        }
+    }
+}
+
+/// A [`StandardBomb`] whose fuse only starts once it is stuck somewhere, so it can be
+/// thrown far and set to go off a while later.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MineBomb(pub StandardBomb);
+
+impl Default for MineBomb
+{
+    fn default() -> Self
+    {
+        Self(StandardBomb { fuse_secs: 5.0, radius: 80.0, impulse: 160_000.0, knockback: 300.0, carve_radius: 24.0, color: css_colors::DARK_RED })
+    }
+}
+
+impl BombType for MineBomb
+{
+    fn fuse_secs(&self) -> f32 { self.0.fuse_secs }
+    fn radius(&self) -> f32 { self.0.radius }
+    fn color(&self) -> Color { self.0.color.into() }
+    fn arms_on_impact(&self) -> bool { true }
+
+    fn detonate(&self, detonation: &BombDetonated, types: &BombTypes, commands: &mut Commands)
+    {
+        self.0.detonate(detonation, types, commands);
+    }
+}
+
+/// Leaves a [`GravityWell`] behind, pulling dynamic bodies in for a while.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GravityWellBomb
+{
+    pub fuse_secs: f32,
+    pub radius: f32,
+    /// Pull at the centre, in pixels per second squared.
+    pub acceleration: f32,
+    pub duration_secs: f32,
+}
+
+impl Default for GravityWellBomb
+{
+    fn default() -> Self
+    {
+        Self { fuse_secs: 3.0, radius: 96.0, acceleration: 600.0, duration_secs: 2.0 }
+    }
+}
+
+impl BombType for GravityWellBomb
+{
+    fn fuse_secs(&self) -> f32 { self.fuse_secs }
+    fn radius(&self) -> f32 { self.radius }
+    fn color(&self) -> Color { css_colors::PURPLE.into() }
+
+    fn detonate(&self, detonation: &BombDetonated, _types: &BombTypes, commands: &mut Commands)
+    {
+        commands.spawn(GravityWell {
+            position: detonation.position,
+            radius: self.radius,
+            acceleration: self.acceleration,
+            timer: Timer::from_seconds(self.duration_secs, TimerMode::Once),
+        });
+    }
+}
+
+/// Turns the dynamic bodies around it [`Frozen`] for a while.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FreezeBomb
+{
+    pub fuse_secs: f32,
+    pub radius: f32,
+    pub duration_secs: f32,
+}
+
+impl Default for FreezeBomb
+{
+    fn default() -> Self
+    {
+        Self { fuse_secs: 3.0, radius: 64.0, duration_secs: 3.0 }
+    }
+}
+
+impl BombType for FreezeBomb
+{
+    fn fuse_secs(&self) -> f32 { self.fuse_secs }
+    fn radius(&self) -> f32 { self.radius }
+    fn color(&self) -> Color { css_colors::LIGHT_BLUE.into() }
+
+    fn detonate(&self, detonation: &BombDetonated, _types: &BombTypes, commands: &mut Commands)
+    {
+        commands.send_event(Freeze { position: detonation.position, radius: self.radius, duration_secs: self.duration_secs });
+    }
+}
+
+/// Pulls the dynamic bodies within `radius` of `position` towards it until its timer runs out.
+/// The pull falls off linearly to zero at `radius`.
+#[derive(Component, Clone, Debug)]
+pub struct GravityWell
+{
+    pub position: Vec2,
+    pub radius: f32,
+    pub acceleration: f32,
+    pub timer: Timer,
+}
+
+/// Freezes the dynamic bodies within `radius` of `position`. See [`Frozen`].
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Freeze
+{
+    pub position: Vec2,
+    pub radius: f32,
+    pub duration_secs: f32,
+}
+
+/// A dynamic body made [`RigidBody::Fixed`] by a [`Freeze`], until its timer runs out
+/// and it carries on with the velocity it had.
+#[derive(Component, Clone, Debug)]
+pub struct Frozen
+{
+    pub timer: Timer,
+    pub velocity: Velocity,
+}
+
+/// The dynamic bodies with a collider within `radius` of `position`, each once, in query order.
+fn dynamic_bodies_within(context: &RapierContext, position: Vec2, radius: f32) -> Vec<Entity>
+{
+    let mut bodies: Vec<Entity> = Vec::new();
+    context.intersections_with_shape(position, 0.0, &Collider::ball(radius), QueryFilter::only_dynamic().exclude_sensors(), |collider| {
+        let body = context.colliders.collider_parent(context.rigidbody_set, collider).unwrap_or(collider);
+        if !bodies.contains(&body) { bodies.push(body); }
+        true
+    });
+    bodies
+}
+
+pub(crate) fn apply_freezes(
+    mut commands: Commands,
+    mut freezes: EventReader<Freeze>,
+    rapier_context: ReadRapierContext,
+    mut bodies: Query<(&mut RigidBody, Option<&Velocity>), Without<Frozen>>,
+)
+{
+    let Ok(context) = rapier_context.single() else { return; };
+
+    for freeze in freezes.read()
+    {
+        for body in dynamic_bodies_within(&context, freeze.position, freeze.radius)
+        {
+            let Ok((mut rigid_body, velocity)) = bodies.get_mut(body) else { continue; };
+            // Frozen earlier this tick, by another freeze.
+            if *rigid_body != RigidBody::Dynamic { continue; }
+
+            debug!("{:?} frozen", body);
+            *rigid_body = RigidBody::Fixed;
+            commands.entity(body).insert(Frozen {
+                timer: Timer::from_seconds(freeze.duration_secs, TimerMode::Once),
+                velocity: velocity.copied().unwrap_or_default(),
+            });
+        }
+    }
+}
+
+pub(crate) fn thaw_frozen_bodies(
+    mut commands: Commands,
+    mut frozen: Query<(Entity, &mut Frozen, &mut RigidBody)>,
+    time: Res<Time>,
+)
+{
+    for (body, mut frozen, mut rigid_body) in frozen.iter_mut()
+    {
+        if !frozen.timer.tick(time.delta()).finished() { continue; }
+
+        debug!("{:?} thawed", body);
+        *rigid_body = RigidBody::Dynamic;
+        commands.entity(body).remove::<Frozen>().insert(frozen.velocity);
+    }
+}
+
+pub(crate) fn pull_into_gravity_wells(
+    mut commands: Commands,
+    mut wells: Query<(Entity, &mut GravityWell)>,
+    rapier_context: ReadRapierContext,
+    mut bodies: Query<(&GlobalTransform, &mut Velocity)>,
+    time: Res<Time>,
+)
+{
+    let Ok(context) = rapier_context.single() else { return; };
+    let dt = time.delta_secs();
+
+    for (well, mut gravity_well) in wells.iter_mut()
+    {
+        for body in dynamic_bodies_within(&context, gravity_well.position, gravity_well.radius)
+        {
+            let Ok((transform, mut velocity)) = bodies.get_mut(body) else { continue; };
+
+            let offset = gravity_well.position - transform.translation().xy();
+            let falloff = 1.0 - offset.length() / gravity_well.radius;
+            if falloff <= 0.0 { continue; }
+            velocity.linvel += offset.normalize_or_zero() * gravity_well.acceleration * falloff * dt;
+        }
+
+        if gravity_well.timer.tick(time.delta()).finished()
+        {
+            commands.entity(well).despawn();
+        }
+    }
+}
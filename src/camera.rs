@@ -0,0 +1,111 @@
+//! A camera that follows the [`Player`] around the level.
+//!
+//! The camera only moves once the player leaves a dead zone around the middle of the view, and looks
+//! ahead in the direction the player faces. It eases towards where it wants to be, stays within the
+//! [`LevelBounds`], and is snapped to whole texels so the pixel-perfect view doesn't shimmer. What's left over
+//! from snapping is the [`CameraFollow::texel_offset`], which the canvas is shifted by to scroll smoothly.
+
+use bevy::prelude::*;
+
+use crate::character::Player;
+use crate::graphics::DEFAULT_RESOLUTION;
+use crate::level::LevelBounds;
+
+/// Makes a camera follow the [`Player`]. Its [`Transform`] is set every frame from [`CameraFollow::position`].
+#[derive(Component, Clone, Debug, PartialEq)]
+#[require(Transform)]
+pub struct CameraFollow
+{
+    /// Half the size of the area around the middle of the view the player can move in without the camera following.
+    pub dead_zone: Vec2,
+    /// How far ahead of the player the camera looks, horizontally in the direction it faces.
+    pub look_ahead: f32,
+    /// Time in seconds the camera takes to cover most (63%) of the way to where it wants to be. Zero keeps up exactly.
+    pub smoothing: f32,
+    /// Size of the view in world pixels, to keep it within the [`LevelBounds`].
+    pub view: Vec2,
+    /// Where the camera is, before snapping to whole texels.
+    pub position: Vec2,
+}
+
+impl Default for CameraFollow
+{
+    fn default() -> Self
+    {
+        Self {
+            dead_zone: Vec2::new(24.0, 32.0),
+            look_ahead: 48.0,
+            smoothing: 0.2,
+            view: DEFAULT_RESOLUTION.as_vec2(),
+            position: Vec2::ZERO,
+        }
+    }
+}
+
+impl CameraFollow
+{
+    /// The point the camera looks at: `target`, looking ahead in the direction it faces.
+    pub fn focus(&self, target: Vec2, flip_x: bool) -> Vec2
+    {
+        target + Vec2::X * if flip_x { -self.look_ahead } else { self.look_ahead }
+    }
+
+    /// Where the camera wants to be: as close as it is now, but with `focus` within the dead zone.
+    pub fn desired_position(&self, focus: Vec2) -> Vec2
+    {
+        let offset = focus - self.position;
+        self.position + offset - offset.clamp(-self.dead_zone, self.dead_zone)
+    }
+
+    /// Moves `position` so the view stays within `bounds`, centring it along any axis the bounds are too small for.
+    pub fn clamp_to(&self, position: Vec2, bounds: Rect) -> Vec2
+    {
+        let half_view = self.view / 2.0;
+        let min = bounds.min + half_view;
+        let max = bounds.max - half_view;
+        Vec2::new(
+            if min.x <= max.x { position.x.clamp(min.x, max.x) } else { bounds.center().x },
+            if min.y <= max.y { position.y.clamp(min.y, max.y) } else { bounds.center().y },
+        )
+    }
+
+    /// How far `position` is from the whole texel the camera is snapped to, between -0.5 and 0.5 along each axis.
+    pub fn texel_offset(&self) -> Vec2
+    {
+        self.position - self.position.round()
+    }
+}
+
+/// Moves each [`CameraFollow`] camera after the player, jumping straight to a newly spawned one.
+pub(crate) fn follow_player(
+    time: Res<Time>,
+    bounds: Option<Res<LevelBounds>>,
+    player: Option<Single<(Ref<Player>, &Transform, Option<&Sprite>), Without<CameraFollow>>>,
+    mut cameras: Query<(&mut CameraFollow, &mut Transform)>,
+)
+{
+    let Some(player) = player else { return; };
+    let (player, player_transform, sprite) = player.into_inner();
+    let flip_x = sprite.is_some_and(|sprite| sprite.flip_x);
+
+    for (mut follow, mut transform) in cameras.iter_mut()
+    {
+        let focus = follow.focus(player_transform.translation.xy(), flip_x);
+        let mut desired = if player.is_added() { focus } else { follow.desired_position(focus) };
+        if let Some(bounds) = &bounds { desired = follow.clamp_to(desired, bounds.0); }
+
+        follow.position = if player.is_added() || follow.smoothing <= 0.0
+        {
+            desired
+        }
+        else
+        {
+            let t = 1.0 - (-time.delta_secs() / follow.smoothing).exp();
+            follow.position.lerp(desired, t)
+        };
+
+        let snapped = follow.position - follow.texel_offset();
+        transform.translation.x = snapped.x;
+        transform.translation.y = snapped.y;
+    }
+}
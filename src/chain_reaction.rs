@@ -0,0 +1,88 @@
+//! Bombs caught in an [`Explosion`] going off too, shortly after.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use bevy::ecs::entity::EntityHashSet;
+
+use crate::bomb::{detonate, Bomb, BombDetonated, BombKind};
+use crate::explosion::Explosion;
+
+/// How long a bomb caught in an explosion takes to go off.
+pub const CHAIN_DELAY_SECS: f32 = 0.15;
+/// Most bombs set off by chain reactions in one tick. The rest wait for the next ones,
+/// so a big field of bombs goes off in waves instead of all in one tick.
+pub const MAX_CHAIN_DETONATIONS_PER_TICK: usize = 8;
+
+/// Bombs caught in explosions, in the order they will go off, each with its delay.
+#[derive(Resource, Debug, Default)]
+pub struct ChainReactions
+{
+    pending: VecDeque<(Entity, Timer)>,
+    queued: EntityHashSet,
+}
+
+impl ChainReactions
+{
+    pub fn len(&self) -> usize
+    {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.pending.is_empty()
+    }
+}
+
+/// Queues every armed bomb within reach of an [`Explosion`], nearest first and then by entity,
+/// and detonates the queued bombs whose delay is up, at most [`MAX_CHAIN_DETONATIONS_PER_TICK`] a tick.
+///
+/// Bombs armed this tick, like the shards of a cluster bomb, are left out.
+pub(crate) fn chain_reactions(
+    mut commands: Commands,
+    mut chains: ResMut<ChainReactions>,
+    mut explosions: EventReader<Explosion>,
+    mut detonations: EventWriter<BombDetonated>,
+    bombs: Query<(Entity, Ref<Bomb>, &BombKind, &GlobalTransform)>,
+    time: Res<Time>,
+)
+{
+    let chains = &mut *chains;
+    for explosion in explosions.read()
+    {
+        let mut caught: Vec<(f32, Entity)> = bombs.iter()
+            .filter(|(bomb, armed, _, _)| !armed.is_added() && !chains.queued.contains(bomb))
+            .map(|(bomb, _, _, transform)| (transform.translation().xy().distance(explosion.position), bomb))
+            .filter(|&(distance, _)| distance <= explosion.radius)
+            .collect();
+        caught.sort_by(|(a_dist, a), (b_dist, b)| a_dist.total_cmp(b_dist).then(a.cmp(b)));
+
+        for (_, bomb) in caught
+        {
+            chains.queued.insert(bomb);
+            chains.pending.push_back((bomb, Timer::from_seconds(CHAIN_DELAY_SECS, TimerMode::Once)));
+        }
+    }
+
+    for (_, timer) in chains.pending.iter_mut()
+    {
+        timer.tick(time.delta());
+    }
+
+    let mut detonated = 0;
+    while detonated < MAX_CHAIN_DETONATIONS_PER_TICK
+    {
+        let Some((bomb, timer)) = chains.pending.front() else { break; };
+        if !timer.finished() { break; }
+        let bomb = *bomb;
+        chains.pending.pop_front();
+        chains.queued.remove(&bomb);
+
+        // Already gone off on its own.
+        let Ok((_, _, kind, transform)) = bombs.get(bomb) else { continue; };
+        detonate(&mut commands, &mut detonations, bomb, kind, transform);
+        detonated += 1;
+    }
+}
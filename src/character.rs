@@ -0,0 +1,303 @@
+use bevy::prelude::*;
+
+use bevy::render::view::RenderLayers;
+use bevy_rapier2d::prelude::*;
+
+use crate::graphics::PIXEL_PERFECT_LAYERS;
+use crate::input::Action;
+use crate::physics::{collision_groups, GROUP_PLAYER};
+use crate::movement::{MovementProfile, MovementProfileHandle};
+
+#[derive(Bundle)]
+pub struct CharacterBundle
+{
+    character: KinematicCharacterController,
+    velocity: Velocity,
+    movement: MovementState,
+    intent: MoveIntent,
+    knockback: Knockback,
+    transform: Transform,
+    sprite: Sprite,
+    collider: Collider,
+    rigid_body: RigidBody,
+    layers: RenderLayers,
+    visibility: Visibility,
+    collision_groups: CollisionGroups,
+}
+
+impl CharacterBundle
+{
+    /// The fields not included have obvious default values. 
+    /// The character is in [`GROUP_PLAYER`]; see [`with_memberships`](Self::with_memberships).
+    pub fn with_non_defaults(character: KinematicCharacterController, sprite: Sprite, collider: Collider, transform: Transform) -> Self
+    {
+        Self
+        {
+            character: KinematicCharacterController {
+                filter_groups: Some(collision_groups(GROUP_PLAYER)),
+                ..character
+            },
+            velocity: Velocity::default(),
+            movement: MovementState::default(),
+            intent: MoveIntent::default(),
+            knockback: Knockback::default(),
+            transform,
+            sprite,
+            collider,
+            rigid_body: RigidBody::KinematicPositionBased,
+            layers: PIXEL_PERFECT_LAYERS,
+            visibility: Visibility::Inherited,
+            collision_groups: collision_groups(GROUP_PLAYER),
+        }
+    }
+
+    /// Puts the character in the `memberships` collision groups instead, for both its
+    /// collider and the movement queries of its controller.
+    pub fn with_memberships(mut self, memberships: Group) -> Self
+    {
+        self.collision_groups = collision_groups(memberships);
+        self.character.filter_groups = Some(self.collision_groups);
+        self
+    }
+}
+
+#[derive(Component)]
+pub struct Player;
+
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Enemy;
+
+/// What a character is trying to do this tick: from input for the [`Player`], from
+/// [`enemy_ai`](crate::enemy::enemy_ai) for an [`Enemy`]. [`move_characters`] carries it out.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct MoveIntent
+{
+    /// Horizontal direction, scaled by how much of the top speed to use: `-1.0` runs left at full speed.
+    pub run: f32,
+    /// Starts a jump, or buffers one until landing.
+    pub jump: bool,
+    /// Keeps a jump going; letting go early cuts it short.
+    pub hold_jump: bool,
+}
+
+/// Per-character state of [`move_characters`], kept across frames.
+///
+/// This is the velocity the character *wants* to move with. Rapier overwrites the
+/// [`Velocity`] of kinematic bodies with the motion that actually happened, so it can't hold this.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct MovementState
+{
+    pub velocity: Vec2,
+    /// Seconds left in which a jump is allowed although the character left the ground.
+    pub coyote_timer: f32,
+    /// Seconds left in which a jump pressed in the air still fires on landing.
+    pub jump_buffer_timer: f32,
+    /// Whether the character is rising from a jump that releasing the button can still cut short.
+    pub jumping: bool,
+    /// What the character looks like it's doing, for its animation.
+    pub pose: Pose,
+    /// Seconds left showing [`Pose::PlaceBomb`].
+    pub place_bomb_timer: f32,
+}
+
+/// How long a character shows [`Pose::PlaceBomb`] after placing or throwing a bomb, in seconds.
+pub const PLACE_BOMB_SECS: f32 = 0.3;
+
+impl MovementState
+{
+    /// Shows [`Pose::PlaceBomb`] for the next [`PLACE_BOMB_SECS`].
+    pub fn place_bomb(&mut self)
+    {
+        self.place_bomb_timer = PLACE_BOMB_SECS;
+    }
+}
+
+/// What a character looks like it's doing, worked out by [`move_characters`] each tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Pose
+{
+    #[default]
+    Idle,
+    Run,
+    Jump,
+    Fall,
+    PlaceBomb,
+}
+
+impl Pose
+{
+    /// The Aseprite tag animating the pose.
+    pub fn tag(self) -> &'static str
+    {
+        match self
+        {
+            Pose::Idle => "idle",
+            Pose::Run => "run",
+            Pose::Jump => "jump",
+            Pose::Fall => "fall",
+            Pose::PlaceBomb => "place-bomb",
+        }
+    }
+}
+
+/// Velocity change waiting to be applied to a kinematic character, e.g. by an [`Explosion`](crate::explosion::Explosion).
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Knockback(pub Vec2);
+
+pub(crate) fn player_intent(
+    actions: Res<ButtonInput<Action>>,
+    mut players: Query<&mut MoveIntent, With<Player>>,
+)
+{
+    let run = match (actions.pressed(Action::MoveLeft), actions.pressed(Action::MoveRight))
+    {
+        (true, _) => -1.0,
+        (false, true) => 1.0,
+        (false, false) => 0.0,
+    };
+    for mut intent in players.iter_mut()
+    {
+        *intent = MoveIntent {
+            run,
+            jump: actions.just_pressed(Action::Jump),
+            hold_jump: actions.pressed(Action::Jump),
+        };
+    }
+}
+
+pub(crate) fn move_characters(
+    mut characters: Query<(Entity, &MoveIntent, &mut MovementState, &mut Sprite, &mut KinematicCharacterController, Option<&KinematicCharacterControllerOutput>, &mut Knockback, Option<&MovementProfileHandle>)>,
+    profiles: Res<Assets<MovementProfile>>,
+    // r_context_mut: Single<&mut RapierContextSimulation>,
+    r_config: Single<&RapierConfiguration>,
+    time: Res<Time>,
+)
+{
+    // println!("Player move system running. Number of player queries found: {:?}", players.iter().count());
+
+    let gravity = r_config.gravity;
+    let default_profile = MovementProfile::default();
+
+    for (entity, intent, mut state, mut spr, mut char, output, mut knockback, profile) in characters.iter_mut()
+    {
+        let profile = profile.and_then(|handle| profiles.get(&handle.0)).unwrap_or(&default_profile);
+
+        let dt = time.delta_secs();
+        let grounded = output.is_some_and(|output| output.grounded);
+        let control = if grounded { 1.0 } else { profile.air_control };
+        let accel = profile.acceleration * control;
+        let decel = profile.deceleration * control;
+        let max_speed = profile.max_speed * intent.run.abs().min(1.0);
+
+        let mut new_vel: f32 = 0.0;
+
+        if intent.run != 0.0
+        {
+            let sign: f32 = intent.run.signum();
+            let mut acc = accel;
+            if state.velocity.x.signum() != sign.signum()
+            {
+                acc = f32::max(accel, decel);
+            }
+            acc *= sign;
+
+            new_vel = state.velocity.x + acc * dt;
+            new_vel = new_vel.clamp(-max_speed, max_speed);
+
+            // new_vel = acc * time.delta_secs();
+            // new_vel = new_vel.clamp(-profile.max_speed, profile.max_speed);
+
+            // new_vel
+        }
+        else 
+        {
+            // let mut new_vel = 0.0;
+            if state.velocity.x.abs() > 0.0
+            {
+                let sign = state.velocity.x.signum();
+                new_vel = state.velocity.x - sign * decel * dt;
+                if new_vel.signum() != sign { new_vel = 0.0; }
+                // if new_vel.signum() != sign { new_vel = -vel.linvel.x * 0.2; }
+            }    
+
+            // new_vel
+        };
+
+        if new_vel < 0.0
+        {
+            spr.flip_x = true;
+        }
+        else if new_vel > 0.0
+        {
+            spr.flip_x = false;
+        }
+
+        // new_vel = new_vel.clamp(-profile.max_speed, profile.max_speed);
+        state.velocity.x = new_vel;
+
+        if intent.jump
+        {
+            state.jump_buffer_timer = profile.jump_buffer;
+        }
+
+        if output.is_some()
+        {
+            if grounded
+            {
+                state.velocity.y = 0.0; // Reset vertical velocity when grounded
+                state.coyote_timer = profile.coyote_time;
+                state.jumping = false;
+            }
+            else
+            {
+                state.velocity += profile.gravity_scale * gravity * dt;
+                state.coyote_timer = (state.coyote_timer - dt).max(0.0);
+            }
+            // println!("Player output: {:#?}", output);
+
+            if state.jump_buffer_timer > 0.0 && (grounded || state.coyote_timer > 0.0)
+            {
+                debug!("{:?} jumped!", entity);
+                state.velocity.y = profile.jump_velocity(gravity);
+                state.jump_buffer_timer = 0.0;
+                state.coyote_timer = 0.0;
+                state.jumping = true;
+            }
+
+            if state.jumping && (state.velocity.y <= 0.0 || !intent.hold_jump)
+            {
+                if state.velocity.y > 0.0 { state.velocity.y *= profile.jump_cut; }
+                state.jumping = false;
+            }
+
+            state.velocity.y = state.velocity.y.max(-profile.max_fall_speed);
+        }
+        state.jump_buffer_timer = (state.jump_buffer_timer - dt).max(0.0);
+
+        // Jumping off the ground counts as airborne straight away.
+        let airborne = output.is_some() && (!grounded || state.velocity.y > 0.0);
+        state.pose = if state.place_bomb_timer > 0.0
+        {
+            Pose::PlaceBomb
+        }
+        else if airborne
+        {
+            if state.velocity.y > 0.0 { Pose::Jump } else { Pose::Fall }
+        }
+        else if state.velocity.x != 0.0
+        {
+            Pose::Run
+        }
+        else
+        {
+            Pose::Idle
+        };
+        state.place_bomb_timer = (state.place_bomb_timer - dt).max(0.0);
+
+        state.velocity += std::mem::take(&mut knockback.0);
+
+        char.translation = Some(state.velocity * dt);
+    }
+
+    
+}
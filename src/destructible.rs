@@ -0,0 +1,231 @@
+//! Terrain that explosions carve holes into.
+//!
+//! A [`TerrainMask`] is a grid of solid or empty cells. Its colliders are the outlines of the
+//! solid cells, traced with marching squares, and split into chunks of [`CHUNK_CELLS`] squared
+//! so that a hole only rebuilds the colliders of the chunks it touched.
+
+use bevy::prelude::*;
+
+use bevy_rapier2d::prelude::*;
+
+use crate::explosion::Explosion;
+use crate::physics::{collision_groups, GROUP_NORMAL_TERRAIN};
+
+/// Width and height of the chunks a [`TerrainMask`]'s colliders are split into, in marching squares.
+pub const CHUNK_CELLS: u32 = 16;
+
+/// Destructible terrain: `width` by `height` cells of `cell_size` pixels, centred on its entity.
+/// Cell `(0, 0)` is the bottom-left one.
+#[derive(Component, Clone, Debug, PartialEq)]
+#[require(Transform, Visibility, RigidBody = RigidBody::Fixed)]
+pub struct TerrainMask
+{
+    width: u32,
+    height: u32,
+    cell_size: f32,
+    solid: Vec<bool>,
+    /// Chunks whose colliders no longer match the cells, row by row.
+    dirty: Vec<bool>,
+}
+
+/// The colliders of one chunk of its parent's [`TerrainMask`].
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TerrainChunk(pub UVec2);
+
+impl TerrainMask
+{
+    /// All solid, every chunk waiting to be built.
+    pub fn solid(width: u32, height: u32, cell_size: f32) -> Self
+    {
+        let mut mask = Self { width, height, cell_size, solid: vec![true; (width * height) as usize], dirty: Vec::new() };
+        mask.dirty = vec![true; (mask.chunks().x * mask.chunks().y) as usize];
+        mask
+    }
+
+    pub fn width(&self) -> u32 { self.width }
+    pub fn height(&self) -> u32 { self.height }
+    pub fn cell_size(&self) -> f32 { self.cell_size }
+
+    /// Size in pixels.
+    pub fn size(&self) -> Vec2
+    {
+        Vec2::new(self.width as f32, self.height as f32) * self.cell_size
+    }
+
+    /// How many chunks there are along each axis. There is one more marching square than
+    /// cells along each axis, since the outline runs around the outside of the edge cells.
+    pub fn chunks(&self) -> UVec2
+    {
+        UVec2::new((self.width + 1).div_ceil(CHUNK_CELLS), (self.height + 1).div_ceil(CHUNK_CELLS))
+    }
+
+    /// Whether cell `(x, y)` is solid. Everything outside the mask is empty.
+    pub fn is_solid(&self, x: i32, y: i32) -> bool
+    {
+        x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height
+            && self.solid[(y as u32 * self.width + x as u32) as usize]
+    }
+
+    /// The cell containing `position`, in the mask's space.
+    pub fn cell_at(&self, position: Vec2) -> IVec2
+    {
+        ((position + self.size() / 2.0) / self.cell_size).floor().as_ivec2()
+    }
+
+    /// Empties the cells whose centres are within `radius` of `centre`, in the mask's space.
+    /// Returns whether any cell changed.
+    pub fn carve_circle(&mut self, centre: Vec2, radius: f32) -> bool
+    {
+        let min = self.cell_at(centre - radius).max(IVec2::ZERO);
+        let max = self.cell_at(centre + radius).min(IVec2::new(self.width as i32 - 1, self.height as i32 - 1));
+        let mut changed = false;
+
+        for y in min.y..=max.y
+        {
+            for x in min.x..=max.x
+            {
+                if !self.is_solid(x, y) || self.cell_centre(x, y).distance(centre) > radius { continue; }
+
+                self.solid[(y as u32 * self.width + x as u32) as usize] = false;
+                // The cell is a corner of the four marching squares around it.
+                for square in [IVec2::new(x, y), IVec2::new(x + 1, y), IVec2::new(x, y + 1), IVec2::new(x + 1, y + 1)]
+                {
+                    let chunk = square.as_uvec2() / CHUNK_CELLS;
+                    let index = chunk.y * self.chunks().x + chunk.x;
+                    self.dirty[index as usize] = true;
+                }
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    pub fn has_dirty_chunks(&self) -> bool
+    {
+        self.dirty.contains(&true)
+    }
+
+    /// The chunks that need rebuilding, marking them clean.
+    pub fn take_dirty_chunks(&mut self) -> Vec<UVec2>
+    {
+        let columns = self.chunks().x;
+        let dirty = self.dirty.iter().enumerate()
+            .filter(|&(_, &dirty)| dirty)
+            .map(|(i, _)| UVec2::new(i as u32 % columns, i as u32 / columns))
+            .collect();
+        self.dirty.fill(false);
+        dirty
+    }
+
+    /// The outline of the solid cells within `chunk`, as separate segments in the mask's space.
+    ///
+    /// Marching square `(sx, sy)` has the centres of cells `sx - 1` to `sx` and `sy - 1` to `sy`
+    /// as its corners, and the outline crosses its edges halfway between a solid and an empty corner.
+    /// Where two opposite corners are solid, they are kept apart.
+    pub fn chunk_segments(&self, chunk: UVec2) -> Vec<[Vec2; 2]>
+    {
+        let mut segments = Vec::new();
+        let start = chunk * CHUNK_CELLS;
+        let end = (start + CHUNK_CELLS).min(UVec2::new(self.width + 1, self.height + 1));
+
+        for sy in start.y as i32..end.y as i32
+        {
+            for sx in start.x as i32..end.x as i32
+            {
+                let (x, y) = (sx - 1, sy - 1);
+                let case = self.is_solid(x, y) as u8
+                    | (self.is_solid(x + 1, y) as u8) << 1
+                    | (self.is_solid(x + 1, y + 1) as u8) << 2
+                    | (self.is_solid(x, y + 1) as u8) << 3;
+
+                let corner = self.cell_centre(x, y);
+                let half = self.cell_size / 2.0;
+                let bottom = corner + Vec2::new(half, 0.0);
+                let right = corner + Vec2::new(self.cell_size, half);
+                let top = corner + Vec2::new(half, self.cell_size);
+                let left = corner + Vec2::new(0.0, half);
+
+                match case
+                {
+                    1 | 14 => segments.push([left, bottom]),
+                    2 | 13 => segments.push([bottom, right]),
+                    3 | 12 => segments.push([left, right]),
+                    4 | 11 => segments.push([right, top]),
+                    5 => segments.extend([[left, bottom], [right, top]]),
+                    6 | 9 => segments.push([bottom, top]),
+                    7 | 8 => segments.push([left, top]),
+                    10 => segments.extend([[bottom, right], [top, left]]),
+                    _ => {}
+                }
+            }
+        }
+        segments
+    }
+
+    fn cell_centre(&self, x: i32, y: i32) -> Vec2
+    {
+        (Vec2::new(x as f32, y as f32) + 0.5) * self.cell_size - self.size() / 2.0
+    }
+}
+
+/// Carves the [`Explosion::carve_radius`] of every explosion out of the terrain masks it reaches.
+pub(crate) fn carve_terrain(
+    mut explosions: EventReader<Explosion>,
+    mut masks: Query<(&mut TerrainMask, &GlobalTransform)>,
+)
+{
+    for explosion in explosions.read()
+    {
+        if explosion.carve_radius <= 0.0 { continue; }
+
+        for (mut mask, transform) in masks.iter_mut()
+        {
+            let centre = transform.affine().inverse().transform_point3(explosion.position.extend(0.0)).xy();
+            // Only touch the mask (and its change detection) when something is carved.
+            if mask.bypass_change_detection().carve_circle(centre, explosion.carve_radius)
+            {
+                mask.set_changed();
+            }
+        }
+    }
+}
+
+/// Rebuilds the colliders of the dirty chunks of every [`TerrainMask`], spawning the chunks on first use.
+/// Chunks take the mask's friction and restitution, and are [`GROUP_NORMAL_TERRAIN`].
+pub(crate) fn rebuild_terrain_colliders(
+    mut commands: Commands,
+    mut masks: Query<(Entity, &mut TerrainMask, Option<&Children>, Option<&Friction>, Option<&Restitution>)>,
+    chunks: Query<&TerrainChunk>,
+)
+{
+    for (entity, mut mask, children, friction, restitution) in masks.iter_mut()
+    {
+        if !mask.has_dirty_chunks() { continue; }
+
+        for chunk in mask.bypass_change_detection().take_dirty_chunks()
+        {
+            let segments = mask.chunk_segments(chunk);
+            let existing = children.and_then(|children| children.iter().find(|&child| chunks.get(child).is_ok_and(|c| c.0 == chunk)));
+            let mut chunk_entity = match existing
+            {
+                Some(child) => commands.entity(child),
+                None =>
+                {
+                    let mut child = commands.spawn((TerrainChunk(chunk), Transform::default(), collision_groups(GROUP_NORMAL_TERRAIN), ChildOf(entity)));
+                    if let Some(friction) = friction { child.insert(*friction); }
+                    if let Some(restitution) = restitution { child.insert(*restitution); }
+                    child
+                }
+            };
+
+            if segments.is_empty()
+            {
+                chunk_entity.remove::<Collider>();
+                continue;
+            }
+            let vertices = segments.iter().flatten().copied().collect();
+            let indices = (0..segments.len() as u32).map(|i| [2 * i, 2 * i + 1]).collect();
+            chunk_entity.insert(Collider::polyline(vertices, Some(indices)));
+        }
+    }
+}
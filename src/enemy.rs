@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+
+use bevy_rapier2d::prelude::*;
+
+use crate::bomb::{Bomb, BombPlaceSpot};
+use crate::character::{Enemy, MoveIntent, Player};
+use crate::physics::GROUP_NORMAL_TERRAIN;
+use crate::sticky::StuckBodies;
+
+/// What an [`Enemy`] is up to.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EnemyState
+{
+    /// Walking back and forth along its [`Patrol`].
+    #[default]
+    Patrol,
+    /// Running at a [`Player`] it can see.
+    Chase,
+    /// Running away from the player in a panic, because a bomb is stuck to it.
+    Flee,
+}
+
+/// The points an [`Enemy`] walks between, in order, while patrolling.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct Patrol
+{
+    pub points: Vec<Vec2>,
+    pub next: usize,
+}
+
+impl Patrol
+{
+    /// Back and forth `distance` pixels either side of `centre`.
+    pub fn around(centre: Vec2, distance: f32) -> Self
+    {
+        Self {
+            points: vec![centre - Vec2::X * distance, centre + Vec2::X * distance],
+            next: 0,
+        }
+    }
+}
+
+const SIGHT_RANGE: f32 = 160.0;
+const WAYPOINT_REACHED: f32 = 4.0;
+// Fractions of the enemy's top speed.
+const PATROL_RUN: f32 = 0.4;
+const CHASE_RUN: f32 = 0.7;
+const FLEE_RUN: f32 = 1.0;
+
+/// Picks each [`Enemy`]'s [`EnemyState`] and turns it into a [`MoveIntent`].
+pub(crate) fn enemy_ai(
+    rapier_context: ReadRapierContext,
+    mut enemies: Query<(Entity, &GlobalTransform, &mut EnemyState, &mut MoveIntent, Option<&mut Patrol>, Option<&KinematicCharacterControllerOutput>, Option<&StuckBodies>), With<Enemy>>,
+    players: Query<&GlobalTransform, With<Player>>,
+    children: Query<&Children>,
+    spots: Query<(), With<BombPlaceSpot>>,
+    bombs: Query<(), With<Bomb>>,
+)
+{
+    let Ok(context) = rapier_context.single() else { return; };
+
+    for (enemy, transform, mut state, mut intent, patrol, output, stuck) in enemies.iter_mut()
+    {
+        let position = transform.translation().xy();
+        let nearest_player = players.iter()
+            .map(|player| player.translation().xy())
+            .min_by(|a, b| a.distance_squared(position).total_cmp(&b.distance_squared(position)));
+
+        let placed_bomb = children.iter_descendants(enemy)
+            .filter(|&spot| spots.contains(spot))
+            .any(|spot| children.get(spot).is_ok_and(|c| c.iter().any(|child| bombs.contains(child))));
+        let thrown_bomb = stuck.is_some_and(|stuck| stuck.iter().any(|body| bombs.contains(body)));
+        let carrying_bomb = placed_bomb || thrown_bomb;
+        let sees_player = nearest_player.is_some_and(|target| {
+            let offset = target - position;
+            let terrain = QueryFilter::only_fixed()
+                .exclude_sensors()
+                .groups(CollisionGroups::new(Group::ALL, GROUP_NORMAL_TERRAIN));
+            offset.length() <= SIGHT_RANGE && context.cast_ray(position, offset, 1.0, true, terrain).is_none()
+        });
+
+        let new_state = if carrying_bomb { EnemyState::Flee } else if sees_player { EnemyState::Chase } else { EnemyState::Patrol };
+        if *state != new_state
+        {
+            debug!("Enemy {:?}: {:?} -> {:?}", enemy, *state, new_state);
+            *state = new_state;
+        }
+
+        let run = match *state
+        {
+            EnemyState::Patrol => match patrol
+            {
+                Some(mut patrol) if !patrol.points.is_empty() =>
+                {
+                    let mut offset = patrol.points[patrol.next].x - position.x;
+                    if offset.abs() <= WAYPOINT_REACHED
+                    {
+                        patrol.next = (patrol.next + 1) % patrol.points.len();
+                        offset = patrol.points[patrol.next].x - position.x;
+                    }
+                    offset.signum() * PATROL_RUN
+                }
+                _ => 0.0,
+            },
+            EnemyState::Chase => nearest_player.map_or(0.0, |target| {
+                let offset = target.x - position.x;
+                if offset.abs() <= WAYPOINT_REACHED { 0.0 } else { offset.signum() * CHASE_RUN }
+            }),
+            // With nobody to run from, keep running the way it was going.
+            EnemyState::Flee => match nearest_player
+            {
+                Some(target) => (position.x - target.x).signum() * FLEE_RUN,
+                None => if intent.run < 0.0 { -FLEE_RUN } else { FLEE_RUN },
+            },
+        };
+
+        // Hop over whatever stopped it last tick, always at full height.
+        let blocked = output.is_some_and(|output| {
+            output.grounded
+            && output.desired_translation.x.abs() > f32::EPSILON
+            && output.effective_translation.x.abs() < output.desired_translation.x.abs() * 0.5
+        });
+        let jump = blocked && run != 0.0;
+
+        *intent = MoveIntent { run, jump, hold_jump: true };
+    }
+}
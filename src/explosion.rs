@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+
+use bevy_rapier2d::prelude::*;
+
+use crate::character::Knockback;
+use crate::physics::GROUP_NORMAL_TERRAIN;
+
+/// A radial blast, written by anything that explodes. See [`apply_explosions`].
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Explosion
+{
+    pub position: Vec2,
+    pub radius: f32,
+    /// Impulse given to dynamic bodies at the center of the blast, falling off linearly to zero at `radius`.
+    pub impulse: f32,
+    /// Velocity given to kinematic characters at the center of the blast, with the same falloff.
+    pub knockback: f32,
+    /// Colliders the blast can reach.
+    pub groups: CollisionGroups,
+    /// Fixed colliders in these groups shield whatever is behind them from the blast.
+    pub blocked_by: Group,
+    /// Radius of the hole carved out of [`TerrainMask`](crate::destructible::TerrainMask)s. Nothing is carved when zero.
+    pub carve_radius: f32,
+}
+
+impl Explosion
+{
+    /// A blast reaching every collider and blocked by normal terrain, carving nothing.
+    pub fn new(position: Vec2, radius: f32, impulse: f32, knockback: f32) -> Self
+    {
+        Self {
+            position,
+            radius,
+            impulse,
+            knockback,
+            groups: CollisionGroups::new(Group::ALL, Group::ALL),
+            blocked_by: GROUP_NORMAL_TERRAIN,
+            carve_radius: 0.0,
+        }
+    }
+}
+
+/// Pushes every body caught in an [`Explosion`] away from its center.
+/// Dynamic bodies receive an [`ExternalImpulse`], kinematic characters a [`Knockback`].
+pub(crate) fn apply_explosions(
+    mut commands: Commands,
+    mut explosions: EventReader<Explosion>,
+    rapier_context: ReadRapierContext,
+    bodies: Query<(&RigidBody, &GlobalTransform)>,
+    mut external_impulses: Query<&mut ExternalImpulse>,
+    mut knockbacks: Query<&mut Knockback>,
+)
+{
+    let Ok(context) = rapier_context.single() else { return; };
+
+    // Several explosions may hit the same body this frame, so sum them up before touching components.
+    let mut impulses: Vec<(Entity, Vec2)> = Vec::new();
+
+    for explosion in explosions.read()
+    {
+        let mut caught: Vec<Entity> = Vec::new();
+        context.intersections_with_shape(
+            explosion.position,
+            0.0,
+            &Collider::ball(explosion.radius),
+            QueryFilter::new().exclude_sensors().groups(explosion.groups),
+            |collider| {
+                let body = context.colliders.collider_parent(context.rigidbody_set, collider).unwrap_or(collider);
+                if !caught.contains(&body) { caught.push(body); }
+                true
+            },
+        );
+
+        // Whatever the blast starts inside of (e.g. the ground a bomb lies on) can't shield anything.
+        let mut embedding: Vec<Entity> = Vec::new();
+        context.intersections_with_point(explosion.position, QueryFilter::new(), |collider| {
+            embedding.push(collider);
+            true
+        });
+
+        for body in caught
+        {
+            let Ok((rigid_body, transform)) = bodies.get(body) else { continue; };
+
+            let offset = transform.translation().xy() - explosion.position;
+            let falloff = 1.0 - offset.length() / explosion.radius;
+            if falloff <= 0.0 { continue; }
+
+            let shields = |collider: Entity| {
+                collider != body
+                && !embedding.contains(&collider)
+                && context.colliders.collider_parent(context.rigidbody_set, collider) != Some(body)
+            };
+            let blocker_filter = QueryFilter::only_fixed()
+                .exclude_sensors()
+                .groups(CollisionGroups::new(Group::ALL, explosion.blocked_by))
+                .predicate(&shields);
+            if context.cast_ray(explosion.position, offset, 1.0, true, blocker_filter).is_some()
+            {
+                continue;
+            }
+
+            let direction = offset.try_normalize().unwrap_or(Vec2::Y);
+            match rigid_body
+            {
+                RigidBody::Dynamic => {
+                    let impulse = direction * explosion.impulse * falloff;
+                    match impulses.iter_mut().find(|(e, _)| *e == body)
+                    {
+                        Some((_, total)) => *total += impulse,
+                        None => impulses.push((body, impulse)),
+                    }
+                },
+                RigidBody::KinematicPositionBased | RigidBody::KinematicVelocityBased => {
+                    if let Ok(mut knockback) = knockbacks.get_mut(body)
+                    {
+                        knockback.0 += direction * explosion.knockback * falloff;
+                    }
+                },
+                RigidBody::Fixed => {},
+            }
+        }
+    }
+
+    for (body, impulse) in impulses
+    {
+        if let Ok(mut external) = external_impulses.get_mut(body)
+        {
+            external.impulse += impulse;
+        }
+        else
+        {
+            commands.entity(body).insert(ExternalImpulse { impulse, torque_impulse: 0.0 });
+        }
+    }
+}
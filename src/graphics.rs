@@ -0,0 +1,330 @@
+use bevy::prelude::*;
+
+use bevy::color::palettes::css as css_colors;
+
+use bevy::asset::RenderAssetUsages;
+use bevy::render::camera::{RenderTarget, ScalingMode, Viewport};
+use bevy::render::render_resource::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages};
+use bevy::render::view::RenderLayers;
+
+use bevy::window::{PrimaryWindow, WindowResized};
+
+use crate::camera::{follow_player, CameraFollow};
+use crate::destructible::TerrainMask;
+use crate::throw::ThrowPreview;
+
+/// The internal resolution of the pixel-perfect world, unless the [`CanvasSettings`] say otherwise.
+pub const DEFAULT_RESOLUTION: UVec2 = UVec2::new(640, 360);
+
+pub const PIXEL_PERFECT_LAYERS: RenderLayers = RenderLayers::layer(0);
+
+pub const HIGH_RES_LAYERS: RenderLayers = RenderLayers::layer(1);
+
+/// Extra texels rendered on each side of the [`Canvas`], hidden until it's shifted for smooth scrolling.
+pub const CANVAS_MARGIN: u32 = 1;
+
+/// Renders the pixel-perfect world at the resolution in the [`CanvasSettings`] and scales it up to the window.
+pub struct PixelCanvasPlugin;
+
+impl Plugin for PixelCanvasPlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        app.init_resource::<CanvasSettings>()
+            .init_resource::<CursorWorldPosition>()
+            .add_systems(Startup, setup_graphics)
+            .add_systems(Update, fit_canvas.run_if(on_event::<WindowResized>.or(resource_changed::<CanvasSettings>)))
+            .add_systems(Update, (draw_throw_previews, draw_terrain_masks))
+            .add_systems(PostUpdate, scroll_canvas.after(follow_player).before(TransformSystem::TransformPropagate))
+            .add_systems(PostUpdate, track_cursor.after(TransformSystem::TransformPropagate));
+    }
+}
+
+/// How the pixel-perfect world is rendered. Insert before adding the [`PixelCanvasPlugin`] to change the resolution;
+/// the scaling can be changed at any time.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct CanvasSettings
+{
+    /// Size of the pixel-perfect world in texels. Only read when the [`Canvas`] is set up.
+    pub resolution: UVec2,
+    pub scaling: CanvasScaling,
+}
+
+impl Default for CanvasSettings
+{
+    fn default() -> Self
+    {
+        Self { resolution: DEFAULT_RESOLUTION, scaling: CanvasScaling::default() }
+    }
+}
+
+/// How the [`Canvas`] is scaled up to fill the window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CanvasScaling
+{
+    /// The largest whole multiple that fits in the window, letterboxed. Every texel is the same size on screen.
+    #[default]
+    Integer,
+    /// As large as fits in the window, letterboxed.
+    Fit,
+    /// Stretched to the window, ignoring the aspect ratio.
+    Stretch,
+    /// As small as fills the window, cropping the edges along one axis.
+    Crop,
+}
+
+impl CanvasScaling
+{
+    /// The projection that shows `resolution` texels in `window`, within its [`viewport`](Self::viewport).
+    pub fn projection(self, resolution: Vec2, window: &Window) -> OrthographicProjection
+    {
+        let mut projection = OrthographicProjection::default_2d();
+        match self
+        {
+            CanvasScaling::Integer =>
+            {
+                projection.scale = 1.0 / self.texel_size(resolution, window).unwrap_or(1.0);
+            }
+            CanvasScaling::Fit =>
+            {
+                projection.scaling_mode = ScalingMode::AutoMin { min_width: resolution.x, min_height: resolution.y };
+            }
+            CanvasScaling::Stretch =>
+            {
+                projection.scaling_mode = ScalingMode::Fixed { width: resolution.x, height: resolution.y };
+            }
+            CanvasScaling::Crop =>
+            {
+                projection.scaling_mode = ScalingMode::AutoMax { max_width: resolution.x, max_height: resolution.y };
+            }
+        }
+        projection
+    }
+
+    /// The part of `window` the [`OuterCamera`] draws to. The letterboxed modes are cropped to `resolution`
+    /// texels in the middle, so the [`CANVAS_MARGIN`] stays hidden; the others fill the whole window.
+    pub fn viewport(self, resolution: Vec2, window: &Window) -> Option<Viewport>
+    {
+        let texel_size = self.texel_size(resolution, window)?;
+        let window_size = window.physical_size();
+        let size = (resolution * texel_size * window.scale_factor()).round().as_uvec2().min(window_size);
+        Some(Viewport { physical_position: (window_size - size) / 2, physical_size: size, ..default() })
+    }
+
+    /// How many logical pixels a texel covers in the letterboxed modes.
+    fn texel_size(self, resolution: Vec2, window: &Window) -> Option<f32>
+    {
+        // The projection scale applies to logical pixels, so the window is measured in them too.
+        let fit = (window.size() / resolution).min_element();
+        match self
+        {
+            CanvasScaling::Integer => Some(fit.floor().max(1.0)),
+            CanvasScaling::Fit => Some(fit),
+            CanvasScaling::Stretch | CanvasScaling::Crop => None,
+        }
+    }
+}
+
+/// Where the cursor is in the pixel-perfect world, or `None` when it's outside the window or the [`Canvas`].
+/// Updated after transforms are propagated, so it matches the frame on screen.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct CursorWorldPosition(pub Option<Vec2>);
+
+/// Low-resolution texture that contains the pixel-perfect world.
+/// Canvas itself is rendered to the high-resolution world.
+#[derive(Component)]
+pub struct Canvas;
+
+/// Camera that renders the pixel-perfect world to the [`Canvas`].
+#[derive(Component)]
+pub struct InGameCamera;
+
+/// Camera that renders the [`Canvas`] (and other graphics on [`HIGH_RES_LAYERS`]) to the screen.
+#[derive(Component)]
+pub struct OuterCamera;
+
+fn setup_graphics(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    settings: Res<CanvasSettings>,
+) 
+{
+    // Add a camera so we can see the debug-render.
+    // commands.spawn(Camera2d::default());
+    let canvas_size = Extent3d {
+        width: settings.resolution.x + 2 * CANVAS_MARGIN,
+        height: settings.resolution.y + 2 * CANVAS_MARGIN,
+        ..default()
+    };
+
+    // This Image serves as a canvas representing the low-resolution game screen
+    let mut canvas = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size: canvas_size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+
+    canvas.resize(canvas_size);
+
+    let image_handle = images.add(canvas);
+
+    commands.spawn((
+        Camera2d,
+        Camera {
+            order: -1,
+            target: RenderTarget::Image(image_handle.clone().into()),
+            clear_color: ClearColorConfig::Custom(css_colors::GRAY.into()),
+            ..default()
+        },
+        Msaa::Off,
+        InGameCamera,
+        CameraFollow { view: settings.resolution.as_vec2(), ..default() },
+        PIXEL_PERFECT_LAYERS,
+    ));
+
+    // spawn the canvas
+    commands.spawn((
+        Sprite::from_image(image_handle),
+        Canvas,
+        HIGH_RES_LAYERS,
+    ));
+
+    // The "outer" camera renders whatever is on `HIGH_RES_LAYERS` to the screen.
+    // here, the canvas and one of the sample sprites will be rendered by this camera
+    commands.spawn((
+        Camera2d, 
+        Msaa::Off, 
+        OuterCamera, 
+        HIGH_RES_LAYERS
+    ));
+}
+
+/// Scales the [`OuterCamera`] projection to fit the window, as the [`CanvasSettings`] say.
+fn fit_canvas(
+    settings: Res<CanvasSettings>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    outer: Single<(&mut Camera, &mut Projection), With<OuterCamera>>,
+) 
+{
+    if window.physical_size().min_element() == 0 { return; }
+    let (mut camera, mut projection) = outer.into_inner();
+    let resolution = settings.resolution.as_vec2();
+    camera.viewport = settings.scaling.viewport(resolution, &window);
+    *projection = Projection::Orthographic(settings.scaling.projection(resolution, &window));
+}
+
+/// Shifts the [`Canvas`] back by the [`CameraFollow::texel_offset`] of the [`InGameCamera`], a fraction of a
+/// texel on screen, so scrolling is smooth even though the camera itself only moves by whole texels.
+fn scroll_canvas(
+    camera: Single<&CameraFollow, With<InGameCamera>>,
+    mut canvas: Single<&mut Transform, With<Canvas>>,
+)
+{
+    // One texel of the canvas is one unit on `HIGH_RES_LAYERS`.
+    let offset = -camera.texel_offset();
+    canvas.translation.x = offset.x;
+    canvas.translation.y = offset.y;
+}
+
+/// Maps the cursor through the [`OuterCamera`] onto the [`Canvas`], then from the canvas through the [`InGameCamera`].
+fn track_cursor(
+    settings: Res<CanvasSettings>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    outer: Single<(&Camera, &GlobalTransform), With<OuterCamera>>,
+    canvas: Single<&GlobalTransform, With<Canvas>>,
+    in_game: Single<&GlobalTransform, With<InGameCamera>>,
+    mut cursor: ResMut<CursorWorldPosition>,
+)
+{
+    let (outer, outer_transform) = *outer;
+    let resolution = settings.resolution.as_vec2();
+
+    cursor.0 = window.cursor_position()
+        .and_then(|position| outer.viewport_to_world_2d(outer_transform, position).ok())
+        .and_then(|on_screen| canvas_to_world(on_screen, resolution, &canvas, &in_game));
+}
+
+/// Where `on_screen`, a point on [`HIGH_RES_LAYERS`], shows in the pixel-perfect world. `None` outside the
+/// `resolution` texels the [`OuterCamera`] shows around the origin, e.g. in the letterbox or on the margin.
+///
+/// One texel of the [`Canvas`] is one unit both on [`HIGH_RES_LAYERS`] and in the world, and the canvas is
+/// centred on what the [`InGameCamera`] sees, so the canvas' local space is the camera's.
+pub fn canvas_to_world(on_screen: Vec2, resolution: Vec2, canvas: &GlobalTransform, in_game_camera: &GlobalTransform) -> Option<Vec2>
+{
+    let half = resolution / 2.0;
+    if on_screen.cmplt(-half).any() || on_screen.cmpgt(half).any() { return None; }
+
+    let on_canvas = canvas.affine().inverse().transform_point3(on_screen.extend(0.0));
+    Some(in_game_camera.transform_point(on_canvas.with_z(0.0)).xy())
+}
+
+/// Every other point of each [`ThrowPreview`] as a dot, on the pixel-perfect layers.
+fn draw_throw_previews(
+    mut gizmos: Gizmos,
+    previews: Query<&ThrowPreview>,
+)
+{
+    for preview in previews.iter()
+    {
+        for &point in preview.points.iter().step_by(2)
+        {
+            gizmos.rect_2d(Isometry2d::from_translation(point), Vec2::ONE, css_colors::WHITE);
+        }
+        if let Some(landing) = preview.landing
+        {
+            gizmos.circle_2d(Isometry2d::from_translation(landing), 4.0, css_colors::ORANGE_RED);
+        }
+    }
+}
+
+/// Paints each [`TerrainMask`] that changed into its own texture, one texel per cell.
+fn draw_terrain_masks(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    masks: Query<(Entity, &TerrainMask, Option<&Sprite>), Changed<TerrainMask>>,
+)
+{
+    let solid = css_colors::SADDLE_BROWN.to_u8_array();
+    for (entity, mask, sprite) in masks.iter()
+    {
+        // Texture rows go top to bottom, mask rows bottom to top.
+        let mut data = Vec::with_capacity((mask.width() * mask.height() * 4) as usize);
+        for y in (0..mask.height() as i32).rev()
+        {
+            for x in 0..mask.width() as i32
+            {
+                data.extend_from_slice(if mask.is_solid(x, y) { &solid } else { &[0; 4] });
+            }
+        }
+        let image = Image::new(
+            Extent3d { width: mask.width(), height: mask.height(), ..default() },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+
+        match sprite
+        {
+            Some(sprite) => { images.insert(&sprite.image, image); },
+            None =>
+            {
+                commands.entity(entity).insert(Sprite {
+                    image: images.add(image),
+                    custom_size: Some(mask.size()),
+                    ..default()
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+
+use crate::bomb::OwnedBombs;
+use crate::character::Player;
+use crate::inventory::BombInventory;
+
+/// Shows each [`Player`]'s [`BombInventory`] over the game, at the window's resolution.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        app.add_systems(Startup, setup_hud)
+            .add_systems(Update, update_bomb_hud);
+    }
+}
+
+/// Text listing the bombs carried, the selected kind marked, and how many are live.
+#[derive(Component)]
+pub struct BombHud;
+
+fn setup_hud(mut commands: Commands)
+{
+    commands.spawn((
+        Text::default(),
+        TextFont::from_font_size(16.0),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            left: Val::Px(8.0),
+            ..default()
+        },
+        BombHud,
+    ));
+}
+
+fn update_bomb_hud(
+    players: Query<(&BombInventory, Option<&OwnedBombs>), With<Player>>,
+    mut hud: Single<&mut Text, With<BombHud>>,
+)
+{
+    let mut text = String::new();
+    for (inventory, owned) in players.iter()
+    {
+        for (i, slot) in inventory.slots.iter().enumerate()
+        {
+            let marker = if i == inventory.selected { '>' } else { ' ' };
+            text += &format!("{} {} {}/{}\n", marker, slot.kind.0, slot.count, inventory.capacity);
+        }
+        text += &format!("  live: {}\n", owned.map_or(0, |owned| owned.len()));
+    }
+    if hud.0 != text
+    {
+        hud.0 = text;
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+/// What the player wants to do, independent of the key or button that asked for it.
+///
+/// Gameplay reads these from a [`ButtonInput<Action>`] resource, which [`update_actions`]
+/// fills from the keyboard and gamepads through the [`InputBindings`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action
+{
+    MoveLeft,
+    MoveRight,
+    Jump,
+    PlaceBomb,
+    /// Detonates every bomb the player owns.
+    Detonate,
+    DetonateOldest,
+    /// Selects the next kind of bomb in the inventory.
+    CycleBomb,
+    /// Held to charge a throw, which is let go on release.
+    Throw,
+    AimUp,
+    AimDown,
+    /// Debug: bounces the [`Ball`](crate::level::Ball) up.
+    BallJump,
+    /// Debug: spins the [`Ball`](crate::level::Ball) anticlockwise.
+    BallSpinLeft,
+    /// Debug: spins the [`Ball`](crate::level::Ball) clockwise.
+    BallSpinRight,
+}
+
+impl Action
+{
+    pub const ALL: [Action; 13] = [
+        Action::MoveLeft, Action::MoveRight, Action::Jump, Action::PlaceBomb, Action::Detonate,
+        Action::DetonateOldest, Action::CycleBomb, Action::Throw, Action::AimUp, Action::AimDown,
+        Action::BallJump, Action::BallSpinLeft, Action::BallSpinRight,
+    ];
+}
+
+/// Which keys and gamepad buttons trigger each [`Action`]. Any one of them is enough.
+///
+/// Saved as RON; fields left out of a file keep their default.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct InputBindings
+{
+    pub keyboard: BTreeMap<Action, Vec<KeyCode>>,
+    pub gamepad: BTreeMap<Action, Vec<GamepadButton>>,
+    /// How far a stick has to be pushed to count: the left stick sideways as [`Action::MoveLeft`] or
+    /// [`Action::MoveRight`], the right stick up or down as [`Action::AimUp`] or [`Action::AimDown`].
+    pub stick_threshold: f32,
+}
+
+impl Default for InputBindings
+{
+    fn default() -> Self
+    {
+        Self {
+            keyboard: BTreeMap::from([
+                (Action::MoveLeft, vec![KeyCode::ArrowLeft]),
+                (Action::MoveRight, vec![KeyCode::ArrowRight]),
+                (Action::Jump, vec![KeyCode::ArrowUp]),
+                (Action::PlaceBomb, vec![KeyCode::ArrowDown]),
+                (Action::Detonate, vec![KeyCode::ShiftRight]),
+                (Action::DetonateOldest, vec![KeyCode::Slash]),
+                (Action::CycleBomb, vec![KeyCode::Enter]),
+                (Action::Throw, vec![KeyCode::ControlRight]),
+                (Action::AimUp, vec![KeyCode::PageUp]),
+                (Action::AimDown, vec![KeyCode::PageDown]),
+                (Action::BallJump, vec![KeyCode::Space]),
+                (Action::BallSpinLeft, vec![KeyCode::KeyA]),
+                (Action::BallSpinRight, vec![KeyCode::KeyD]),
+            ]),
+            gamepad: BTreeMap::from([
+                (Action::MoveLeft, vec![GamepadButton::DPadLeft]),
+                (Action::MoveRight, vec![GamepadButton::DPadRight]),
+                (Action::Jump, vec![GamepadButton::South]),
+                (Action::PlaceBomb, vec![GamepadButton::West]),
+                (Action::Detonate, vec![GamepadButton::East]),
+                (Action::DetonateOldest, vec![GamepadButton::North]),
+                (Action::CycleBomb, vec![GamepadButton::RightTrigger]),
+                (Action::Throw, vec![GamepadButton::RightTrigger2]),
+                (Action::AimUp, vec![GamepadButton::DPadUp]),
+                (Action::AimDown, vec![GamepadButton::DPadDown]),
+            ]),
+            stick_threshold: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum InputBindingsError
+{
+    #[error("could not access input bindings: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse input bindings: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("could not write input bindings: {0}")]
+    Write(#[from] ron::Error),
+}
+
+impl InputBindings
+{
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, InputBindingsError>
+    {
+        Ok(ron::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// The bindings saved at `path`, or the defaults if there are none or they can't be read.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Self
+    {
+        let path = path.as_ref();
+        match Self::load(path)
+        {
+            Ok(bindings) => bindings,
+            Err(InputBindingsError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) =>
+            {
+                println!("Using default input bindings, {:?} is unusable: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), InputBindingsError>
+    {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Makes `key` the only key triggering `action`.
+    pub fn bind_key(&mut self, action: Action, key: KeyCode)
+    {
+        self.keyboard.insert(action, vec![key]);
+    }
+
+    /// Makes `button` the only gamepad button triggering `action`.
+    pub fn bind_button(&mut self, action: Action, button: GamepadButton)
+    {
+        self.gamepad.insert(action, vec![button]);
+    }
+
+    fn is_pressed(&self, action: Action, keyboard: &ButtonInput<KeyCode>, gamepads: &Query<&Gamepad>) -> bool
+    {
+        let key = self.keyboard.get(&action).is_some_and(|keys| keyboard.any_pressed(keys.iter().copied()));
+        let button = self.gamepad.get(&action).is_some_and(|buttons| gamepads.iter().any(|pad| pad.any_pressed(buttons.iter().copied())));
+        let stick = gamepads.iter().any(|pad| match action
+        {
+            Action::MoveLeft => pad.left_stick().x <= -self.stick_threshold,
+            Action::MoveRight => pad.left_stick().x >= self.stick_threshold,
+            Action::AimUp => pad.right_stick().y >= self.stick_threshold,
+            Action::AimDown => pad.right_stick().y <= -self.stick_threshold,
+            _ => false,
+        });
+        key || button || stick
+    }
+}
+
+/// Where the [`InputBindings`] are persisted. Without it, rebinding only lasts until the game exits.
+#[derive(Resource, Clone, Debug)]
+pub struct InputBindingsFile(pub PathBuf);
+
+/// The [`Action`] whose binding is replaced by the next key or gamepad button pressed, if any.
+///
+/// Input is captured by the rebind while it is pending: no action is pressed, and the key only
+/// counts once released so it doesn't also trigger its new action.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct PendingRebind(pub Option<Action>);
+
+/// Resolves the [`InputBindings`] into the [`ButtonInput<Action>`] once per gameplay tick,
+/// so an action is just pressed on the first tick it is held.
+pub(crate) fn update_actions(
+    mut actions: ResMut<ButtonInput<Action>>,
+    bindings: Res<InputBindings>,
+    pending: Res<PendingRebind>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+)
+{
+    actions.clear();
+    for action in Action::ALL
+    {
+        if pending.0.is_none() && bindings.is_pressed(action, &keyboard, &gamepads)
+        {
+            actions.press(action);
+        }
+        else
+        {
+            actions.release(action);
+        }
+    }
+}
+
+/// Binds the [`PendingRebind`] action to the first key or gamepad button pressed and released.
+pub(crate) fn capture_rebind(
+    mut pending: ResMut<PendingRebind>,
+    mut bindings: ResMut<InputBindings>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+)
+{
+    let Some(action) = pending.0 else { return; };
+
+    if let Some(&key) = keyboard.get_just_released().next()
+    {
+        bindings.bind_key(action, key);
+    }
+    else if let Some(&button) = gamepads.iter().find_map(|pad| pad.get_just_released().next())
+    {
+        bindings.bind_button(action, button);
+    }
+    else
+    {
+        return;
+    }
+
+    println!("Rebound {:?}", action);
+    pending.0 = None;
+}
+
+/// Writes the [`InputBindings`] to the [`InputBindingsFile`] whenever they change.
+pub(crate) fn save_input_bindings(
+    bindings: Res<InputBindings>,
+    file: Res<InputBindingsFile>,
+)
+{
+    if !bindings.is_changed() || bindings.is_added() { return; }
+
+    if let Err(e) = bindings.save(&file.0)
+    {
+        println!("Could not save input bindings to {:?}: {}", file.0, e);
+    }
+}
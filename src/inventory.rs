@@ -0,0 +1,160 @@
+//! The bombs each [`Player`] carries, and the pickups that refill them.
+
+use bevy::prelude::*;
+
+use bevy::color::palettes::css as css_colors;
+use bevy_rapier2d::prelude::*;
+
+use crate::bomb::BombKind;
+use crate::character::Player;
+use crate::input::Action;
+use crate::physics::{collision_groups, GROUP_PICKUP};
+use crate::sensor::{SensorBundle, SensorContact, SensorInteraction};
+
+/// How many bombs of one kind a [`BombInventory`] holds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BombSlot
+{
+    pub kind: BombKind,
+    pub count: u32,
+}
+
+/// The bombs a [`Player`] can place or throw, one slot per kind, of which one is selected.
+///
+/// Slots stay once they run out, so the order they are cycled through doesn't change.
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+pub struct BombInventory
+{
+    pub slots: Vec<BombSlot>,
+    pub selected: usize,
+    /// Most bombs of one kind carried at once.
+    pub capacity: u32,
+}
+
+impl BombInventory
+{
+    pub fn new(capacity: u32) -> Self
+    {
+        Self { slots: Vec::new(), selected: 0, capacity }
+    }
+
+    /// Adds `count` bombs of `kind` on creation.
+    pub fn with(mut self, kind: BombKind, count: u32) -> Self
+    {
+        self.add(kind, count);
+        self
+    }
+
+    pub fn count(&self, kind: &BombKind) -> u32
+    {
+        self.slots.iter().find(|slot| &slot.kind == kind).map_or(0, |slot| slot.count)
+    }
+
+    pub fn selected(&self) -> Option<&BombSlot>
+    {
+        self.slots.get(self.selected)
+    }
+
+    /// Takes one bomb of the selected kind, if there are any left.
+    pub fn take_selected(&mut self) -> Option<BombKind>
+    {
+        let slot = self.slots.get_mut(self.selected).filter(|slot| slot.count > 0)?;
+        slot.count -= 1;
+        Some(slot.kind.clone())
+    }
+
+    /// Adds up to `count` bombs of `kind` without going over the capacity. Returns how many fit.
+    pub fn add(&mut self, kind: BombKind, count: u32) -> u32
+    {
+        let index = match self.slots.iter().position(|slot| slot.kind == kind)
+        {
+            Some(index) => index,
+            None =>
+            {
+                self.slots.push(BombSlot { kind, count: 0 });
+                self.slots.len() - 1
+            }
+        };
+        let slot = &mut self.slots[index];
+        let added = count.min(self.capacity.saturating_sub(slot.count));
+        slot.count += added;
+        added
+    }
+
+    /// Selects the next kind with bombs left, wrapping around.
+    pub fn cycle(&mut self)
+    {
+        let len = self.slots.len();
+        if let Some(next) = (1..=len).map(|i| (self.selected + i) % len).find(|&i| self.slots[i].count > 0)
+        {
+            self.selected = next;
+        }
+    }
+}
+
+/// Bombs lying around for a [`Player`] to pick up into their [`BombInventory`].
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+pub struct BombPickup
+{
+    pub kind: BombKind,
+    pub count: u32,
+}
+
+const PICKUP_RADIUS: f32 = 6.0;
+
+/// A [`BombPickup`] of `count` bombs of `kind`, sensed by players walking through it.
+pub fn bomb_pickup(kind: BombKind, count: u32, position: Vec2) -> impl Bundle
+{
+    (
+        BombPickup { kind, count },
+        SensorBundle {
+            sensor: Sensor,
+            collider: Collider::ball(PICKUP_RADIUS),
+            active_events: ActiveEvents::COLLISION_EVENTS,
+            collision_groups: collision_groups(GROUP_PICKUP),
+            transform: Transform::from_translation(position.extend(0.0)),
+            visibility: Visibility::Inherited,
+            active_collision_types: ActiveCollisionTypes::all(),
+        },
+        Sprite::from_color(css_colors::GOLD, [6.0, 6.0].into()),
+    )
+}
+
+/// Moves the bombs of each [`BombPickup`] a [`Player`] touches into their [`BombInventory`].
+/// The pickup is gone once empty; whatever didn't fit stays behind.
+pub(crate) fn collect_pickups(
+    mut commands: Commands,
+    mut contacts: EventReader<SensorContact<Player, BombPickup>>,
+    mut inventories: Query<&mut BombInventory, With<Player>>,
+    mut pickups: Query<&mut BombPickup>,
+)
+{
+    for contact in contacts.read()
+    {
+        if contact.interaction != SensorInteraction::Entered { continue; }
+        let (Ok(mut inventory), Ok(mut pickup)) = (inventories.get_mut(contact.a), pickups.get_mut(contact.b)) else { continue; };
+        if pickup.count == 0 { continue; }
+
+        let added = inventory.add(pickup.kind.clone(), pickup.count);
+        pickup.count -= added;
+        debug!("{:?} picked up {} {:?} bombs", contact.a, added, pickup.kind.0);
+        if pickup.count == 0
+        {
+            commands.entity(contact.b).despawn();
+        }
+    }
+}
+
+/// Selects the next kind of bomb on [`Action::CycleBomb`].
+pub(crate) fn cycle_bombs(
+    actions: Res<ButtonInput<Action>>,
+    mut inventories: Query<&mut BombInventory, With<Player>>,
+)
+{
+    if !actions.just_pressed(Action::CycleBomb) { return; }
+
+    for mut inventory in inventories.iter_mut()
+    {
+        inventory.cycle();
+    }
+}
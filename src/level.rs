@@ -0,0 +1,402 @@
+use bevy::prelude::*;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::color::palettes::css as css_colors;
+use serde::Deserialize;
+
+use bevy_rapier2d::prelude::*;
+
+use crate::aseprite::{is_aseprite, AsepriteAnimation};
+use crate::bomb::{Bomb, BombKind, BombPlaceSpotBundle, BombPlacerBundle, BombPreview};
+use crate::chain_reaction::ChainReactions;
+use crate::character::{CharacterBundle, Enemy, Player};
+use crate::destructible::TerrainMask;
+use crate::enemy::{EnemyState, Patrol};
+use crate::inventory::{bomb_pickup, BombInventory};
+use crate::movement::MovementProfileHandle;
+use crate::physics::{collision_groups, GROUP_ENEMY, GROUP_NORMAL_TERRAIN, GROUP_PROP};
+use crate::throw::BombThrower;
+
+#[derive(Component)]
+pub struct Ball;
+
+pub const DEFAULT_LEVEL: &str = "levels/default.level.ron";
+
+/// Everything placed in a level, loaded from `*.level.ron` files. Positions are in pixels,
+/// `y` pointing up. Lists left out of a file are empty, and most fields have defaults.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Level
+{
+    pub terrain: Vec<Terrain>,
+    pub destructible: Vec<DestructibleTerrain>,
+    pub players: Vec<PlayerSpawn>,
+    pub enemies: Vec<EnemySpawn>,
+    pub balls: Vec<BallSpawn>,
+    /// Bomb place spots that aren't attached to anything.
+    pub place_spots: Vec<PlaceSpotSpawn>,
+    pub pickups: Vec<PickupSpawn>,
+    /// The area the camera keeps within. Unbounded when left out.
+    pub bounds: Option<Rect>,
+}
+
+/// A piece of fixed terrain in [`GROUP_NORMAL_TERRAIN`].
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Terrain
+{
+    pub position: Vec2,
+    pub shape: TerrainShape,
+    #[serde(default = "default_terrain_friction")]
+    pub friction: f32,
+    #[serde(default = "default_terrain_restitution")]
+    pub restitution: f32,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub enum TerrainShape
+{
+    Rect { half_extents: Vec2 },
+    /// Points relative to the terrain's position.
+    Polyline { points: Vec<Vec2> },
+}
+
+/// A solid block of destructible terrain, made of `size` cells of `cell_size` pixels. See [`TerrainMask`].
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct DestructibleTerrain
+{
+    pub position: Vec2,
+    pub size: UVec2,
+    #[serde(default = "default_cell_size")]
+    pub cell_size: f32,
+    #[serde(default = "default_terrain_friction")]
+    pub friction: f32,
+    #[serde(default = "default_terrain_restitution")]
+    pub restitution: f32,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct PlayerSpawn
+{
+    pub position: Vec2,
+    /// Path of the player's image, or of an Aseprite file animated by its [`Pose`](crate::character::Pose).
+    #[serde(default = "default_player_sprite")]
+    pub sprite: String,
+    /// Path of the player's [`MovementProfile`](crate::movement::MovementProfile).
+    #[serde(default = "default_player_movement")]
+    pub movement: String,
+    #[serde(default = "default_placer_radius")]
+    pub placer_radius: f32,
+    /// The bombs the player starts with, per kind, in the order they are cycled through.
+    #[serde(default = "default_player_bombs")]
+    pub bombs: Vec<(BombKind, u32)>,
+    /// Most bombs of one kind the player can carry.
+    #[serde(default = "default_bomb_capacity")]
+    pub bomb_capacity: u32,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct EnemySpawn
+{
+    pub position: Vec2,
+    /// Radius of the bomb place spot on the enemy, if bombs can be placed on it.
+    #[serde(default)]
+    pub place_spot: Option<f32>,
+    /// How far either side of its spawn the enemy patrols.
+    #[serde(default = "default_patrol_distance")]
+    pub patrol_distance: f32,
+    #[serde(default = "default_enemy_movement")]
+    pub movement: String,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct BallSpawn
+{
+    pub position: Vec2,
+    #[serde(default = "default_ball_radius")]
+    pub radius: f32,
+    #[serde(default = "default_ball_restitution")]
+    pub restitution: f32,
+    #[serde(default = "default_ball_friction")]
+    pub friction: f32,
+    #[serde(default = "default_ball_damping")]
+    pub damping: f32,
+    /// Radius of the bomb place spot on the ball, if bombs can be placed on it.
+    #[serde(default)]
+    pub place_spot: Option<f32>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct PlaceSpotSpawn
+{
+    pub position: Vec2,
+    pub radius: f32,
+}
+
+/// A [`BombPickup`](crate::inventory::BombPickup).
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct PickupSpawn
+{
+    pub position: Vec2,
+    #[serde(default)]
+    pub kind: BombKind,
+    #[serde(default = "default_pickup_count")]
+    pub count: u32,
+}
+
+fn default_terrain_friction() -> f32 { 0.5 }
+fn default_terrain_restitution() -> f32 { 0.5 }
+fn default_cell_size() -> f32 { 4.0 }
+fn default_player_sprite() -> String { "guy.aseprite".into() }
+fn default_player_movement() -> String { "movement/player.movement.ron".into() }
+fn default_placer_radius() -> f32 { 16.0 }
+fn default_player_bombs() -> Vec<(BombKind, u32)> { vec![(BombKind::STANDARD, 5)] }
+fn default_bomb_capacity() -> u32 { 9 }
+fn default_pickup_count() -> u32 { 3 }
+fn default_patrol_distance() -> f32 { 48.0 }
+fn default_enemy_movement() -> String { "movement/enemy.movement.ron".into() }
+fn default_ball_radius() -> f32 { 12.0 }
+fn default_ball_restitution() -> f32 { 0.7 }
+fn default_ball_friction() -> f32 { 0.5 }
+fn default_ball_damping() -> f32 { 0.9 }
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LevelLoaderError
+{
+    #[error("could not read level: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse level: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for LevelLoader
+{
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error>
+    {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str]
+    {
+        &["level.ron"]
+    }
+}
+
+/// The level being played. It is spawned once loaded, and respawned whenever it changes.
+#[derive(Resource, Clone, Debug)]
+pub struct CurrentLevel(pub Handle<Level>);
+
+/// The [`Level::bounds`] of the current level, if it has any.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct LevelBounds(pub Rect);
+
+/// Marks the top-level entities spawned from the [`CurrentLevel`], which are despawned along with it.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct LevelEntity;
+
+/// Whether gameplay can start: the [`CurrentLevel`], if there is one, has been spawned.
+pub fn level_ready(current: Option<Res<CurrentLevel>>, spawned: Query<(), With<LevelEntity>>) -> bool
+{
+    current.is_none() || !spawned.is_empty()
+}
+
+/// Spawns the [`CurrentLevel`] once it's loaded. Respawning it first clears out the old level, along with
+/// every [`Bomb`] still around and the [`ChainReactions`] waiting to set them off.
+pub(crate) fn spawn_current_level(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<Level>>,
+    current: Option<Res<CurrentLevel>>,
+    levels: Res<Assets<Level>>,
+    spawned: Query<Entity, With<LevelEntity>>,
+    bombs: Query<Entity, With<Bomb>>,
+    mut chains: ResMut<ChainReactions>,
+    assets: Res<AssetServer>,
+)
+{
+    let Some(current) = current else { return; };
+
+    let mut respawn = current.is_changed();
+    for event in events.read()
+    {
+        respawn |= event.is_loaded_with_dependencies(&current.0) || event.is_modified(&current.0);
+    }
+    if !respawn { return; }
+    let Some(level) = levels.get(&current.0) else { return; };
+
+    for entity in spawned.iter()
+    {
+        commands.entity(entity).despawn();
+    }
+    // Placed bombs are already gone with their spot.
+    for bomb in bombs.iter()
+    {
+        commands.entity(bomb).try_despawn();
+    }
+    *chains = ChainReactions::default();
+    level.spawn(&mut commands, &assets);
+}
+
+impl Level
+{
+    /// Spawns everything in the level, each top-level entity marked with [`LevelEntity`], and sets the [`LevelBounds`].
+    pub fn spawn(&self, commands: &mut Commands, assets: &AssetServer)
+    {
+        match self.bounds
+        {
+            Some(bounds) => commands.insert_resource(LevelBounds(bounds)),
+            None => commands.remove_resource::<LevelBounds>(),
+        }
+
+        for terrain in &self.terrain
+        {
+            let collider = match &terrain.shape
+            {
+                TerrainShape::Rect { half_extents } => Collider::cuboid(half_extents.x, half_extents.y),
+                TerrainShape::Polyline { points } => Collider::polyline(points.clone(), None),
+            };
+            commands.spawn((
+                LevelEntity,
+                collider,
+                Transform::from_translation(terrain.position.extend(0.0)),
+                RigidBody::Fixed,
+                collision_groups(GROUP_NORMAL_TERRAIN),
+                Friction::coefficient(terrain.friction),
+                Restitution::coefficient(terrain.restitution),
+            ));
+        }
+
+        for terrain in &self.destructible
+        {
+            commands.spawn((
+                LevelEntity,
+                TerrainMask::solid(terrain.size.x, terrain.size.y, terrain.cell_size),
+                Transform::from_translation(terrain.position.extend(0.0)),
+                Friction::coefficient(terrain.friction),
+                Restitution::coefficient(terrain.restitution),
+            ));
+        }
+
+        for ball in &self.balls
+        {
+            let mut entity = commands.spawn((
+                LevelEntity,
+                Ball,
+                RigidBody::Dynamic,
+                Velocity::default(),
+                Collider::ball(ball.radius),
+                collision_groups(GROUP_PROP),
+                Restitution::coefficient(ball.restitution),
+                Transform::from_translation(ball.position.extend(0.0)),
+                Friction::coefficient(ball.friction),
+                Damping {
+                    linear_damping: ball.damping,
+                    angular_damping: ball.damping,
+                },
+            ));
+            if let Some(radius) = ball.place_spot
+            {
+                entity.with_children(|parent| spawn_place_spot(parent, radius));
+            }
+        }
+
+        for player in &self.players
+        {
+            let inventory = player.bombs.iter()
+                .fold(BombInventory::new(player.bomb_capacity), |inventory, (kind, count)| inventory.with(kind.clone(), *count));
+            let mut entity = commands.spawn((
+                LevelEntity,
+                Player,
+                inventory,
+                BombThrower::default(),
+                MovementProfileHandle(assets.load(&player.movement)),
+                CharacterBundle::with_non_defaults(
+                    KinematicCharacterController {
+                        slide: true,
+                        autostep: Some(CharacterAutostep {
+                            include_dynamic_bodies: true,
+                            max_height: CharacterLength::Relative(0.25),
+                            min_width: CharacterLength::Relative(0.5),
+                        }),
+                        snap_to_ground: None,
+                        apply_impulse_to_dynamic_bodies: true,
+                        ..default()
+                    },
+                    if is_aseprite(&player.sprite) { Sprite::default() } else { Sprite::from_image(assets.load(&player.sprite)) },
+                    Collider::cuboid(6.0, 8.0),
+                    Transform::from_translation(player.position.extend(0.0)),
+                ),
+            ));
+            entity.with_children(|ent|
+            {
+                ent.spawn(BombPlacerBundle::ball_with_radius(player.placer_radius));
+            });
+            if is_aseprite(&player.sprite)
+            {
+                entity.insert(AsepriteAnimation::new(assets.load(&player.sprite)));
+            }
+        }
+
+        for enemy in &self.enemies
+        {
+            let mut entity = commands.spawn((
+                LevelEntity,
+                Enemy,
+                EnemyState::default(),
+                Patrol::around(enemy.position, enemy.patrol_distance),
+                MovementProfileHandle(assets.load(&enemy.movement)),
+                CharacterBundle::with_non_defaults(
+                    KinematicCharacterController {
+                        slide: true,
+                        snap_to_ground: None,
+                        apply_impulse_to_dynamic_bodies: true,
+                        ..default()
+                    },
+                    Sprite::from_color(css_colors::BLUE_VIOLET, [12.0, 16.0].into()),
+                    Collider::cuboid(6.0, 8.0),
+                    Transform::from_translation(enemy.position.extend(0.0)),
+                ).with_memberships(GROUP_ENEMY),
+            ));
+            if let Some(radius) = enemy.place_spot
+            {
+                entity.with_children(|parent| spawn_place_spot(parent, radius));
+            }
+        }
+
+        for spot in &self.place_spots
+        {
+            commands.spawn((LevelEntity, Transform::from_translation(spot.position.extend(0.0)), Visibility::default()))
+                .with_children(|parent| spawn_place_spot(parent, spot.radius));
+        }
+
+        for pickup in &self.pickups
+        {
+            commands.spawn((LevelEntity, bomb_pickup(pickup.kind.clone(), pickup.count, pickup.position)));
+        }
+    }
+}
+
+/// A [`BombPlaceSpotBundle`] with its hidden [`BombPreview`].
+fn spawn_place_spot(parent: &mut ChildSpawnerCommands, radius: f32)
+{
+    parent.spawn(BombPlaceSpotBundle::ball_with_radius(radius))
+    .with_child((
+        Sprite::from_color(css_colors::DARK_RED, [8.0, 8.0].into()),
+        Visibility::Hidden,
+        BombPreview,
+        Transform::default(),
+    ));
+}
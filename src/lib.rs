@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use bevy_rapier2d::prelude::*;
+
+pub mod aseprite;
+pub mod bomb;
+pub mod bomb_type;
+pub mod camera;
+pub mod chain_reaction;
+pub mod character;
+pub mod destructible;
+pub mod enemy;
+pub mod explosion;
+pub mod graphics;
+pub mod hud;
+pub mod input;
+pub mod inventory;
+pub mod level;
+pub mod movement;
+pub mod physics;
+pub mod replay;
+pub mod sensor;
+pub mod sticky;
+pub mod throw;
+pub mod tiled;
+
+use aseprite::*;
+use bomb::*;
+use bomb_type::*;
+use camera::*;
+use chain_reaction::*;
+use character::*;
+use destructible::*;
+use enemy::*;
+use explosion::*;
+use input::*;
+use inventory::*;
+use level::*;
+use movement::*;
+use replay::*;
+use sensor::*;
+use sticky::*;
+use throw::*;
+use tiled::*;
+
+/// Length of one gameplay tick. Gameplay and physics run in [`FixedUpdate`] so that a run
+/// only depends on the actions held on each tick; see [`replay`].
+pub const TIMESTEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// All of the gameplay: input actions, physics, movement, sensors and bombs.
+///
+/// Needs no window or renderer, so it also runs under [`MinimalPlugins`] (plus a
+/// [`TransformPlugin`] and an [`AssetPlugin`]). Nothing is spawned until a [`CurrentLevel`] is set.
+pub struct StickyBombsGamePlugin;
+
+/// The gameplay systems in [`FixedUpdate`], all of which run before the physics step.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameplaySet;
+
+impl Plugin for StickyBombsGamePlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        app.add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(physics::PIXELS_PER_METER).in_fixed_schedule())
+            .insert_resource(Time::<Fixed>::from_duration(TIMESTEP))
+            .insert_resource(TimestepMode::Fixed { dt: TIMESTEP.as_secs_f32(), substeps: 1 })
+            // Normally provided by the `InputPlugin`; headless apps press keys on it directly.
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ButtonInput<Action>>()
+            .init_resource::<InputBindings>()
+            .init_resource::<PendingRebind>()
+            .init_resource::<ChainReactions>()
+            .add_systems(PreUpdate, capture_rebind.after(bevy::input::InputSystem))
+            .add_systems(FixedPreUpdate, (
+                update_actions.run_if(not(resource_exists::<InputReplay>)),
+                // Only count ticks once the level is in, so a replay starts on the same tick however long loading takes.
+                replay_actions.run_if(resource_exists::<InputReplay>.and(level_ready)),
+                record_actions.run_if(resource_exists::<InputRecorder>.and(level_ready)),
+            ).chain())
+            .add_systems(PostUpdate, save_input_bindings.run_if(resource_exists::<InputBindingsFile>))
+            .add_systems(Last, save_recording_on_exit.run_if(resource_exists::<InputRecorder>))
+            .init_asset::<MovementProfile>()
+            .init_asset_loader::<MovementProfileLoader>()
+            .init_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .init_asset_loader::<TiledLoader>()
+            .add_systems(Update, spawn_current_level)
+            .init_asset::<Aseprite>()
+            .init_asset_loader::<AsepriteLoader>()
+            .add_systems(Update, (animate_poses, animate_sprites).chain())
+            .add_systems(PostUpdate, follow_player.before(TransformSystem::TransformPropagate))
+            .add_event::<SensorEvent>()
+            .add_event::<BombDetonated>()
+            .add_event::<Explosion>()
+            .add_event::<Freeze>()
+            .register_bomb_type(BombKind::STANDARD, StandardBomb::default())
+            .register_bomb_type(BombKind::CLUSTER, ClusterBomb::default())
+            .register_bomb_type(BombKind::CLUSTER_SHARD, ClusterBomb::default_shard())
+            .register_bomb_type(BombKind::MINE, MineBomb::default())
+            .register_bomb_type(BombKind::GRAVITY_WELL, GravityWellBomb::default())
+            .register_bomb_type(BombKind::FREEZE, FreezeBomb::default())
+            // Every gameplay system is ordered, so a tick plays out the same way each run.
+            .configure_sets(FixedUpdate, GameplaySet.before(PhysicsSet::SyncBackend))
+            .configure_sets(FixedUpdate, SensorRouting.after(diverge_collision_events).in_set(GameplaySet))
+            .add_systems(FixedUpdate, diverge_collision_events.in_set(GameplaySet))
+            .add_sensor_route::<BombPromixityPlacer, BombPlaceSpot>()
+            .add_sensor_route::<Player, BombPickup>()
+            .add_systems(FixedUpdate, (track_proximity_overlaps, select_place_spots, update_bomb_previews, collect_pickups, cycle_bombs).chain().after(SensorRouting).in_set(GameplaySet))
+            .add_systems(FixedUpdate, (stick_on_contact, place_bombs, throw_bombs, preview_throws, detonate_bombs, tick_bomb_fuses, chain_reactions, set_off_detonations, apply_explosions, carve_terrain, rebuild_terrain_colliders, apply_freezes, thaw_frozen_bodies, pull_into_gravity_wells, player_intent, enemy_ai, move_characters).chain().after(cycle_bombs).in_set(GameplaySet))
+            .add_systems(FixedUpdate, (follow_hosts, fall_off_despawned_hosts).chain().after(PhysicsSet::Writeback));
+    }
+}
@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use serde::Deserialize;
+
+/// How a character moves: its acceleration, top speed, jump and gravity.
+///
+/// Loaded from `*.movement.ron` files so feel can be tuned without recompiling, and
+/// hot-reloaded when the `hot_reload` feature is on. Fields left out of a file keep their default.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct MovementProfile
+{
+    /// Horizontal acceleration while a direction is held, in px/s².
+    pub acceleration: f32,
+    /// Horizontal deceleration when no direction is held or when turning around, in px/s².
+    pub deceleration: f32,
+    /// Fraction of `acceleration` and `deceleration` available while airborne.
+    pub air_control: f32,
+    /// Top horizontal speed, in px/s.
+    pub max_speed: f32,
+    /// Height of a jump, in px. The jump velocity is derived from it and the effective gravity.
+    pub jump_height: f32,
+    /// Multiplier on the physics gravity while airborne.
+    pub gravity_scale: f32,
+    /// Top falling speed, in px/s.
+    pub max_fall_speed: f32,
+    /// How long after walking off a ledge a jump is still allowed, in seconds.
+    pub coyote_time: f32,
+    /// How long a jump pressed before landing is remembered, in seconds.
+    pub jump_buffer: f32,
+    /// Multiplier on upward velocity when the jump is released early, for short hops.
+    pub jump_cut: f32,
+}
+
+impl Default for MovementProfile
+{
+    fn default() -> Self
+    {
+        Self {
+            acceleration: 360.0,
+            deceleration: 1000.0,
+            air_control: 1.0,
+            max_speed: 160.0,
+            jump_height: 46.0,
+            gravity_scale: 2.0,
+            max_fall_speed: 320.0,
+            coyote_time: 0.1,
+            jump_buffer: 0.12,
+            jump_cut: 0.5,
+        }
+    }
+}
+
+impl MovementProfile
+{
+    /// Vertical velocity reaching `jump_height` under `gravity`.
+    pub fn jump_velocity(&self, gravity: Vec2) -> f32
+    {
+        (2.0 * self.gravity_scale * gravity.length() * self.jump_height).sqrt()
+    }
+}
+
+/// The [`MovementProfile`] a character moves with. Characters without one, or whose
+/// profile is still loading, use [`MovementProfile::default`].
+#[derive(Component, Clone, Debug, Default)]
+pub struct MovementProfileHandle(pub Handle<MovementProfile>);
+
+#[derive(Default)]
+pub struct MovementProfileLoader;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MovementProfileLoaderError
+{
+    #[error("could not read movement profile: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse movement profile: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for MovementProfileLoader
+{
+    type Asset = MovementProfile;
+    type Settings = ();
+    type Error = MovementProfileLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error>
+    {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str]
+    {
+        &["movement.ron"]
+    }
+}
@@ -0,0 +1,56 @@
+use bevy_rapier2d::prelude::*;
+
+pub const PIXELS_PER_METER: f32 = 8.0;
+
+// Constants for collision groups and layers
+pub const GROUP_NORMAL_TERRAIN:         Group = Group::GROUP_1;
+pub const GROUP_PLAYER:                 Group = Group::GROUP_2;
+pub const GROUP_ENEMY:                  Group = Group::GROUP_3;
+pub const GROUP_BOMB:                   Group = Group::GROUP_4;
+pub const GROUP_DEBRIS:                 Group = Group::GROUP_5;
+/// Loose dynamic bodies, like the [`Ball`](crate::level::Ball).
+pub const GROUP_PROP:                   Group = Group::GROUP_6;
+/// [`BombPickup`](crate::inventory::BombPickup) sensors.
+pub const GROUP_PICKUP:                 Group = Group::GROUP_30;
+pub const GROUP_PROXIMITY_PLACE_SPOT:   Group = Group::GROUP_31;
+pub const GROUP_PROXIMITY_PLACER:       Group = Group::GROUP_32;
+
+/// The collision matrix: the groups each group interacts with.
+///
+/// Rapier only lets two colliders interact when each one's memberships are in the other's
+/// filters, so every row here is mirrored by the rows it names.
+/// - Players don't collide with bombs, so they can't be blocked by the bombs they throw.
+/// - Debris only rests on terrain and props, and never triggers sensors.
+/// - Pickups are only sensed by players.
+/// - Proximity sensors only see each other.
+const COLLISION_MATRIX: [(Group, Group); 9] = [
+    (GROUP_NORMAL_TERRAIN,          GROUP_PLAYER.union(GROUP_ENEMY).union(GROUP_BOMB).union(GROUP_DEBRIS).union(GROUP_PROP)),
+    (GROUP_PLAYER,                  GROUP_NORMAL_TERRAIN.union(GROUP_ENEMY).union(GROUP_PROP).union(GROUP_PICKUP)),
+    (GROUP_ENEMY,                   GROUP_NORMAL_TERRAIN.union(GROUP_PLAYER).union(GROUP_ENEMY).union(GROUP_BOMB).union(GROUP_PROP)),
+    (GROUP_BOMB,                    GROUP_NORMAL_TERRAIN.union(GROUP_ENEMY).union(GROUP_BOMB).union(GROUP_PROP)),
+    (GROUP_DEBRIS,                  GROUP_NORMAL_TERRAIN.union(GROUP_DEBRIS).union(GROUP_PROP)),
+    (GROUP_PROP,                    GROUP_NORMAL_TERRAIN.union(GROUP_PLAYER).union(GROUP_ENEMY).union(GROUP_BOMB).union(GROUP_DEBRIS).union(GROUP_PROP)),
+    (GROUP_PICKUP,                  GROUP_PLAYER),
+    (GROUP_PROXIMITY_PLACE_SPOT,    GROUP_PROXIMITY_PLACER),
+    (GROUP_PROXIMITY_PLACER,        GROUP_PROXIMITY_PLACE_SPOT),
+];
+
+/// The groups that colliders in `memberships` interact with, according to the [collision matrix](COLLISION_MATRIX).
+pub const fn collision_filters(memberships: Group) -> Group
+{
+    let mut filters = Group::NONE;
+    let mut i = 0;
+    while i < COLLISION_MATRIX.len()
+    {
+        let (group, row) = COLLISION_MATRIX[i];
+        if memberships.intersects(group) { filters = filters.union(row); }
+        i += 1;
+    }
+    filters
+}
+
+/// [`CollisionGroups`] for a collider in `memberships`, filtered by the collision matrix.
+pub const fn collision_groups(memberships: Group) -> CollisionGroups
+{
+    CollisionGroups::new(memberships, collision_filters(memberships))
+}
@@ -0,0 +1,164 @@
+//! Recording and replaying the [`Action`]s held on each gameplay tick.
+//!
+//! Gameplay only reads input through [`ButtonInput<Action>`] and runs on the fixed [`TIMESTEP`](crate::TIMESTEP),
+//! so feeding a recording back into the same level reproduces the run exactly.
+
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use crate::input::Action;
+
+/// The [`Action`]s held on each tick, stored as runs of `(ticks, held)` since they rarely change.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct InputRecording
+{
+    pub runs: Vec<(u32, Vec<Action>)>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum InputRecordingError
+{
+    #[error("could not access input recording: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse input recording: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("could not write input recording: {0}")]
+    Write(#[from] ron::Error),
+}
+
+impl InputRecording
+{
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, InputRecordingError>
+    {
+        Ok(ron::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), InputRecordingError>
+    {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Appends one tick.
+    pub fn push(&mut self, held: Vec<Action>)
+    {
+        match self.runs.last_mut()
+        {
+            Some((ticks, last)) if *last == held => *ticks += 1,
+            _ => self.runs.push((1, held)),
+        }
+    }
+
+    /// Number of ticks recorded.
+    pub fn len(&self) -> u32
+    {
+        self.runs.iter().map(|(ticks, _)| ticks).sum()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len() == 0
+    }
+
+    /// The actions held on each tick, in order.
+    pub fn ticks(&self) -> impl Iterator<Item = &[Action]>
+    {
+        self.runs.iter().flat_map(|(ticks, held)| std::iter::repeat_n(held.as_slice(), *ticks as usize))
+    }
+}
+
+/// Records the [`Action`]s of every tick, and saves them to `path` (if any) when the app exits.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct InputRecorder
+{
+    pub recording: InputRecording,
+    pub path: Option<PathBuf>,
+}
+
+impl InputRecorder
+{
+    pub fn to_file(path: impl Into<PathBuf>) -> Self
+    {
+        Self { recording: InputRecording::default(), path: Some(path.into()) }
+    }
+}
+
+/// Plays an [`InputRecording`] back instead of reading the keyboard and gamepads.
+/// Once it runs out, no action is held.
+#[derive(Resource, Clone, Debug)]
+pub struct InputReplay
+{
+    recording: InputRecording,
+    run: usize,
+    tick_in_run: u32,
+}
+
+impl InputReplay
+{
+    pub fn new(recording: InputRecording) -> Self
+    {
+        Self { recording, run: 0, tick_in_run: 0 }
+    }
+
+    pub fn finished(&self) -> bool
+    {
+        self.run >= self.recording.runs.len()
+    }
+
+    fn next(&mut self) -> &[Action]
+    {
+        let Some((ticks, held)) = self.recording.runs.get(self.run) else { return &[]; };
+
+        self.tick_in_run += 1;
+        if self.tick_in_run >= *ticks
+        {
+            self.run += 1;
+            self.tick_in_run = 0;
+        }
+        held
+    }
+}
+
+pub(crate) fn replay_actions(
+    mut actions: ResMut<ButtonInput<Action>>,
+    mut replay: ResMut<InputReplay>,
+)
+{
+    let was_finished = replay.finished();
+    let held = replay.next().to_vec();
+    if replay.finished() && !was_finished { println!("Replay finished"); }
+
+    actions.clear();
+    for action in Action::ALL
+    {
+        if held.contains(&action) { actions.press(action); } else { actions.release(action); }
+    }
+}
+
+pub(crate) fn record_actions(
+    actions: Res<ButtonInput<Action>>,
+    mut recorder: ResMut<InputRecorder>,
+)
+{
+    let held = Action::ALL.into_iter().filter(|&action| actions.pressed(action)).collect();
+    recorder.recording.push(held);
+}
+
+pub(crate) fn save_recording_on_exit(
+    mut exits: EventReader<AppExit>,
+    recorder: Res<InputRecorder>,
+)
+{
+    if exits.read().next().is_none() { return; }
+    let Some(path) = &recorder.path else { return; };
+
+    match recorder.recording.save(path)
+    {
+        Ok(()) => println!("Saved {} ticks of input to {:?}", recorder.recording.len(), path),
+        Err(e) => println!("Could not save input recording to {:?}: {}", path, e),
+    }
+}
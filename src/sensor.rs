@@ -0,0 +1,124 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+use bevy::platform::collections::HashSet;
+use bevy_rapier2d::rapier::prelude::CollisionEventFlags;
+use bevy_rapier2d::prelude::*;
+
+#[derive(Bundle, Clone, Debug)]
+pub struct SensorBundle
+{
+    pub sensor: Sensor,
+    pub collider: Collider,
+    pub active_events: ActiveEvents,
+    pub collision_groups: CollisionGroups,
+    pub transform: Transform,
+    pub visibility: Visibility,
+    pub active_collision_types: ActiveCollisionTypes,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SensorEvent(pub Entity, pub Entity, pub SensorInteraction);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SensorInteraction
+{
+    Entered,
+    Exited,
+}
+
+/// A [`SensorEvent`] between an entity with `A` and an entity with `B`, already ordered by role.
+/// Registered with [`SensorRoutingAppExt::add_sensor_route`].
+#[derive(Event)]
+pub struct SensorContact<A: Component, B: Component>
+{
+    pub a: Entity,
+    pub b: Entity,
+    pub interaction: SensorInteraction,
+    _roles: PhantomData<fn() -> (A, B)>,
+}
+
+/// Systems turning [`SensorEvent`]s into [`SensorContact`]s. Sensor handlers run after it.
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SensorRouting;
+
+pub trait SensorRoutingAppExt
+{
+    /// Routes every [`SensorEvent`] between an `A` and a `B` into a [`SensorContact<A, B>`].
+    fn add_sensor_route<A: Component, B: Component>(&mut self) -> &mut Self;
+}
+
+impl SensorRoutingAppExt for App
+{
+    fn add_sensor_route<A: Component, B: Component>(&mut self) -> &mut Self
+    {
+        self.add_event::<SensorContact<A, B>>()
+            .add_systems(FixedUpdate, route_sensor_events::<A, B>.in_set(SensorRouting))
+    }
+}
+
+pub(crate) fn diverge_collision_events(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut sensor_event_writer: EventWriter<SensorEvent>,
+    // mut commands: Commands,
+) 
+{
+    for event in collision_events.read() {
+        trace!("Collision event: {:?}", event);
+
+        match *event {
+            CollisionEvent::Started(a, b, t) => {
+                if t.contains(CollisionEventFlags::SENSOR)
+                {
+                    sensor_event_writer.write(SensorEvent(a, b, SensorInteraction::Entered));
+                }
+            }
+            // Also sent with `REMOVED` when one of the colliders is despawned.
+            CollisionEvent::Stopped(a, b, t) => {
+                if t.contains(CollisionEventFlags::SENSOR)
+                {
+                    sensor_event_writer.write(SensorEvent(a, b, SensorInteraction::Exited));
+                }
+            }
+        }
+    }
+}
+
+// Sensor events will occur when at least one of the colliders involved in the collision is a sensor.
+// 
+// We need to split up these events for the variety of use-cases we may have for sensors.
+// a. Bomb placement, by proximity to the player, typically
+//
+// Each use-case registers its own route, so handlers never have to work out which entity is which.
+pub(crate) fn route_sensor_events<A: Component, B: Component>(
+    mut sensor_events: EventReader<SensorEvent>,
+    mut contacts: EventWriter<SensorContact<A, B>>,
+    role_a: Query<(), With<A>>,
+    role_b: Query<(), With<B>>,
+    // Pairs that entered, so their exit is still routed once one of them has been despawned.
+    mut active: Local<HashSet<(Entity, Entity)>>,
+)
+{
+    for &SensorEvent(first, second, interaction) in sensor_events.read()
+    {
+        let pair = match interaction
+        {
+            SensorInteraction::Entered => {
+                let pair =
+                    if role_a.contains(first) && role_b.contains(second) { (first, second) }
+                    else if role_a.contains(second) && role_b.contains(first) { (second, first) }
+                    else { continue; };
+                active.insert(pair);
+                pair
+            },
+            SensorInteraction::Exited => {
+                if active.remove(&(first, second)) { (first, second) }
+                else if active.remove(&(second, first)) { (second, first) }
+                else { continue; }
+            },
+        };
+
+        contacts.write(SensorContact { a: pair.0, b: pair.1, interaction, _roles: PhantomData });
+    }
+}
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+
+use bevy_rapier2d::rapier::prelude::CollisionEventFlags;
+use bevy_rapier2d::prelude::*;
+
+/// A flying body that sticks to the first solid thing it touches. Needs [`ActiveEvents::COLLISION_EVENTS`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Sticky;
+
+/// Holds a stuck body in place on its host, at the pose it touched it with.
+///
+/// While stuck, the body and its collider are disabled and it follows the host around.
+/// When the host is despawned this is removed, and the body falls off with the host's last velocity.
+#[derive(Component, Clone, Debug)]
+#[relationship(relationship_target = StuckBodies)]
+pub struct StuckTo
+{
+    #[relationship]
+    pub host: Entity,
+    /// The stuck body's pose in the host's space.
+    pub offset: Transform,
+}
+
+/// Everything [`StuckTo`] this host.
+#[derive(Component, Clone, Debug, Default)]
+#[relationship_target(relationship = StuckTo)]
+pub struct StuckBodies(Vec<Entity>);
+
+/// Velocity of the point a stuck body sits at on its host, handed over when it falls off.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct HostVelocity(pub Velocity);
+
+/// Where an entity is in the world after the latest physics step.
+///
+/// Its own [`GlobalTransform`] is only propagated before the next step, so it's built from its parent's
+/// instead. That covers colliders nested under a body that doesn't move, like the chunks of a
+/// [`TerrainMask`](crate::destructible::TerrainMask).
+fn world_pose(transform: &Transform, child_of: Option<&ChildOf>, globals: &Query<&GlobalTransform>) -> GlobalTransform
+{
+    let parent = child_of.and_then(|child_of| globals.get(child_of.parent()).ok()).copied().unwrap_or_default();
+    parent.mul_transform(*transform)
+}
+
+/// Sticks each [`Sticky`] body to the first non-sensor collider it starts touching.
+pub(crate) fn stick_on_contact(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    rapier_context: ReadRapierContext,
+    sticky: Query<(&Transform, Option<&ChildOf>), With<Sticky>>,
+    hosts: Query<(&Transform, Option<&ChildOf>)>,
+    globals: Query<&GlobalTransform>,
+)
+{
+    let Ok(context) = rapier_context.single() else { return; };
+    let mut stuck: Vec<Entity> = Vec::new();
+
+    for event in collision_events.read()
+    {
+        let CollisionEvent::Started(a, b, flags) = *event else { continue; };
+        if flags.contains(CollisionEventFlags::SENSOR) { continue; }
+
+        for (body, other) in [(a, b), (b, a)]
+        {
+            let Ok((transform, child_of)) = sticky.get(body) else { continue; };
+            // Two flying bodies bounce off each other.
+            if stuck.contains(&body) || sticky.contains(other) { continue; }
+
+            let host = context.colliders.collider_parent(context.rigidbody_set, other).unwrap_or(other);
+            let Ok((host_transform, host_child_of)) = hosts.get(host) else { continue; };
+
+            let offset = world_pose(transform, child_of, &globals)
+                .reparented_to(&world_pose(host_transform, host_child_of, &globals));
+            debug!("{:?} stuck to {:?}", body, host);
+            commands.entity(body)
+                .remove::<Sticky>()
+                .insert((StuckTo { host, offset }, HostVelocity::default(), RigidBodyDisabled, ColliderDisabled));
+            stuck.push(body);
+        }
+    }
+}
+
+/// Moves stuck bodies along with their hosts. Runs after the physics step, so they match
+/// where their hosts have just been moved to. Stuck bodies are never parented, so their [`Transform`] is
+/// their place in the world.
+pub(crate) fn follow_hosts(
+    mut stuck: Query<(&StuckTo, &mut Transform, &mut HostVelocity)>,
+    hosts: Query<(&Transform, Option<&ChildOf>, Option<&Velocity>), Without<StuckTo>>,
+    globals: Query<&GlobalTransform>,
+)
+{
+    for (stuck_to, mut transform, mut host_velocity) in stuck.iter_mut()
+    {
+        let Ok((host_transform, child_of, velocity)) = hosts.get(stuck_to.host) else { continue; };
+        let host_pose = world_pose(host_transform, child_of, &globals);
+
+        *transform = host_pose.mul_transform(stuck_to.offset).compute_transform();
+
+        let velocity = velocity.copied().unwrap_or_default();
+        let arm = transform.translation.xy() - host_pose.translation().xy();
+        host_velocity.0 = Velocity {
+            linvel: velocity.linvel + velocity.angvel * arm.perp(),
+            angvel: velocity.angvel,
+        };
+    }
+}
+
+/// Bodies whose host went away fall off as dynamic bodies again.
+pub(crate) fn fall_off_despawned_hosts(
+    mut commands: Commands,
+    mut unstuck: RemovedComponents<StuckTo>,
+    stuck: Query<&HostVelocity, Without<StuckTo>>,
+)
+{
+    for body in unstuck.read()
+    {
+        let Ok(&HostVelocity(velocity)) = stuck.get(body) else { continue; };
+
+        debug!("{:?} fell off", body);
+        commands.entity(body)
+            .remove::<(HostVelocity, RigidBodyDisabled, ColliderDisabled)>()
+            .insert(velocity);
+    }
+}
@@ -0,0 +1,156 @@
+//! Throwing bombs: aimed with [`Action::AimUp`] and [`Action::AimDown`], charged by holding
+//! [`Action::Throw`] and let go on release, with a preview of where the bomb will land.
+
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+use bevy::prelude::*;
+
+use bevy_rapier2d::prelude::*;
+
+use crate::bomb::{armed_bomb, flying_bomb, take_bomb, BombOwner, BOMB_RADIUS};
+use crate::bomb_type::BombTypes;
+use crate::character::MovementState;
+use crate::input::Action;
+use crate::inventory::BombInventory;
+use crate::physics::{collision_groups, GROUP_BOMB};
+
+/// Lets a [`Player`](crate::character::Player) throw [`Bomb`](crate::bomb::Bomb)s the way it is facing.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+#[require(ThrowPreview)]
+pub struct BombThrower
+{
+    /// Angle above the horizontal, in radians, between [`MIN_AIM`] and [`MAX_AIM`].
+    pub aim: f32,
+    /// How long the throw has been held, from 0 to 1, picking a speed between
+    /// [`MIN_THROW_SPEED`] and [`MAX_THROW_SPEED`].
+    pub charge: f32,
+}
+
+impl Default for BombThrower
+{
+    fn default() -> Self
+    {
+        Self { aim: FRAC_PI_4, charge: 0.0 }
+    }
+}
+
+impl BombThrower
+{
+    /// Velocity the bomb leaves with, facing left when `flip_x` is set.
+    pub fn velocity(&self, flip_x: bool) -> Vec2
+    {
+        let mut direction = Vec2::from_angle(self.aim);
+        if flip_x { direction.x = -direction.x; }
+        direction * MIN_THROW_SPEED.lerp(MAX_THROW_SPEED, self.charge)
+    }
+}
+
+/// The path a [`BombThrower`]'s bomb would take if let go now, one point per tick.
+/// Empty unless a throw is being charged.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct ThrowPreview
+{
+    pub points: Vec<Vec2>,
+    /// Where the bomb would first touch something, if it does before its fuse runs out.
+    pub landing: Option<Vec2>,
+}
+
+pub const MIN_AIM: f32 = -FRAC_PI_4;
+pub const MAX_AIM: f32 = FRAC_PI_2;
+/// Radians per second.
+const AIM_SPEED: f32 = 1.5;
+const CHARGE_SECS: f32 = 1.0;
+pub const MIN_THROW_SPEED: f32 = 60.0;
+/// Slow enough that a full-strength throw lands before its fuse runs out.
+pub const MAX_THROW_SPEED: f32 = 150.0;
+/// How far ahead the preview looks: as long as a fuse burns.
+const PREVIEW_TICKS: usize = 180;
+
+/// Aims and charges each [`BombThrower`], and throws a bomb from its [`BombInventory`]
+/// when [`Action::Throw`] is let go.
+pub(crate) fn throw_bombs(
+    mut commands: Commands,
+    actions: Res<ButtonInput<Action>>,
+    mut throwers: Query<(Entity, &mut BombThrower, &GlobalTransform, &Sprite)>,
+    mut inventories: Query<&mut BombInventory>,
+    mut states: Query<&mut MovementState>,
+    types: Res<BombTypes>,
+    time: Res<Time>,
+)
+{
+    let dt = time.delta_secs();
+    let aim = (actions.pressed(Action::AimUp) as i32 - actions.pressed(Action::AimDown) as i32) as f32;
+
+    for (entity, mut thrower, transform, sprite) in throwers.iter_mut()
+    {
+        thrower.aim = (thrower.aim + aim * AIM_SPEED * dt).clamp(MIN_AIM, MAX_AIM);
+
+        if actions.pressed(Action::Throw)
+        {
+            thrower.charge = (thrower.charge + dt / CHARGE_SECS).min(1.0);
+        }
+        else if actions.just_released(Action::Throw)
+        {
+            let kind = take_bomb(&mut inventories, entity);
+            let bomb_type = kind.as_ref().and_then(|kind| types.get_or_warn(kind));
+            if let (Some(kind), Some(bomb_type)) = (kind, bomb_type)
+            {
+                let position = transform.translation().xy();
+                let velocity = thrower.velocity(sprite.flip_x);
+                debug!("Threw {:?} bomb from {:?} at {:?}", kind.0, position, velocity);
+                commands.spawn((armed_bomb(kind, bomb_type), flying_bomb(position, velocity), BombOwner(entity)));
+                if let Ok(mut state) = states.get_mut(entity) { state.place_bomb(); }
+            }
+            thrower.charge = 0.0;
+        }
+    }
+}
+
+/// Fills in the [`ThrowPreview`] of every thrower charging a throw.
+///
+/// The path is stepped the way Rapier integrates a falling body, under the current
+/// [`RapierConfiguration::gravity`], and each step is shape cast with the bomb's collider
+/// so the path ends where the bomb would hit something.
+pub(crate) fn preview_throws(
+    actions: Res<ButtonInput<Action>>,
+    rapier_context: ReadRapierContext,
+    config: Query<&RapierConfiguration>,
+    mut throwers: Query<(&BombThrower, &GlobalTransform, &Sprite, &mut ThrowPreview)>,
+    time: Res<Time>,
+)
+{
+    let charging = actions.pressed(Action::Throw);
+    let (Ok(context), Ok(config)) = (rapier_context.single(), config.single()) else { return; };
+    let dt = time.delta_secs();
+    let shape = Collider::ball(BOMB_RADIUS);
+    // Hits exactly what a thrown bomb collides with.
+    let filter = QueryFilter::new()
+        .exclude_sensors()
+        .groups(collision_groups(GROUP_BOMB));
+
+    for (thrower, transform, sprite, mut preview) in throwers.iter_mut()
+    {
+        preview.points.clear();
+        preview.landing = None;
+        if !charging { continue; }
+
+        let mut position = transform.translation().xy();
+        let mut velocity = thrower.velocity(sprite.flip_x);
+        preview.points.push(position);
+
+        for _ in 0..PREVIEW_TICKS
+        {
+            velocity += config.gravity * dt;
+            let hit = context.cast_shape(position, 0.0, velocity, &shape, ShapeCastOptions::with_max_time_of_impact(dt), filter);
+            if let Some((_, hit)) = hit
+            {
+                position += velocity * hit.time_of_impact;
+                preview.points.push(position);
+                preview.landing = Some(position);
+                break;
+            }
+            position += velocity * dt;
+            preview.points.push(position);
+        }
+    }
+}
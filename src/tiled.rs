@@ -0,0 +1,246 @@
+//! Importing [Tiled](https://www.mapeditor.org/) maps, saved as JSON (`*.tmj`), as [`Level`]s.
+//!
+//! Every tile of a tile layer is solid, unless the layer has a `collision` property set to `false`.
+//! Solid tiles are merged into as few rectangles as possible before becoming [`Terrain`].
+//! Objects become spawns by their class (`Player`, `Enemy`, `Ball`, `BombPlaceSpot` or `BombPickup`), and their
+//! custom properties fill in the fields of [`PlayerSpawn`], [`EnemySpawn`], [`BallSpawn`], [`PlaceSpotSpawn`]
+//! or [`PickupSpawn`].
+//!
+//! The map is centred on the origin, with `y` pointing up, and its edges are the level's bounds.
+
+use bevy::prelude::*;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::level::{BallSpawn, EnemySpawn, Level, PickupSpawn, PlaceSpotSpawn, PlayerSpawn, Terrain, TerrainShape};
+
+#[derive(Deserialize)]
+struct TiledMap
+{
+    width: u32,
+    height: u32,
+    tilewidth: f32,
+    tileheight: f32,
+    layers: Vec<TiledLayer>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum TiledLayer
+{
+    TileLayer
+    {
+        name: String,
+        width: u32,
+        height: u32,
+        #[serde(default)]
+        data: Option<Vec<u32>>,
+        #[serde(default)]
+        properties: Vec<TiledProperty>,
+    },
+    ObjectGroup
+    {
+        objects: Vec<TiledObject>,
+    },
+    Group
+    {
+        layers: Vec<TiledLayer>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct TiledObject
+{
+    #[serde(default)]
+    name: String,
+    /// Tiled 1.9 saves this as `class`, while earlier and later versions save it as `type`, so both are read.
+    #[serde(default, alias = "type")]
+    class: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    /// Tile objects are anchored at their bottom-left corner instead of their top-left.
+    #[serde(default)]
+    gid: Option<u32>,
+    #[serde(default)]
+    properties: Vec<TiledProperty>,
+}
+
+#[derive(Deserialize)]
+struct TiledProperty
+{
+    name: String,
+    value: Value,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TiledError
+{
+    #[error("could not read Tiled map: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse Tiled map: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("tile layer {0:?} has no plain tile data; save it as CSV, in a map that isn't infinite")]
+    Encoding(String),
+    #[error("tile layer {layer:?} has {found} tiles instead of its {width}x{height}")]
+    LayerSize { layer: String, width: u32, height: u32, found: usize },
+    #[error("object {name:?} ({class}) has invalid properties: {source}")]
+    Object { name: String, class: String, source: serde_json::Error },
+}
+
+/// Converts a Tiled JSON map into a [`Level`].
+pub fn level_from_tiled(bytes: &[u8]) -> Result<Level, TiledError>
+{
+    let map: TiledMap = serde_json::from_slice(bytes)?;
+    let tile_size = Vec2::new(map.tilewidth, map.tileheight);
+    // From Tiled pixels (origin top-left, y down) to level pixels (origin at the centre, y up).
+    let origin = Vec2::new(map.width as f32, map.height as f32) * tile_size / 2.0;
+    let to_level = |x: f32, y: f32| Vec2::new(x - origin.x, origin.y - y);
+
+    let mut layers = Vec::new();
+    flatten(&map.layers, &mut layers);
+
+    let mut level = Level { bounds: Some(Rect::from_center_size(Vec2::ZERO, origin * 2.0)), ..default() };
+    for layer in layers
+    {
+        match layer
+        {
+            TiledLayer::TileLayer { name, width, height, data, properties } =>
+            {
+                if properties.iter().any(|p| p.name == "collision" && p.value == Value::Bool(false)) { continue; }
+                let data = data.as_ref().ok_or_else(|| TiledError::Encoding(name.clone()))?;
+                if data.len() != (*width * *height) as usize
+                {
+                    return Err(TiledError::LayerSize { layer: name.clone(), width: *width, height: *height, found: data.len() });
+                }
+                let solid: Vec<bool> = data.iter().map(|&gid| gid != 0).collect();
+
+                for rect in merge_tiles(&solid, *width as usize, *height as usize)
+                {
+                    let min = Vec2::new(rect.min.x as f32, rect.min.y as f32) * tile_size;
+                    let size = Vec2::new(rect.width() as f32, rect.height() as f32) * tile_size;
+                    let centre = min + size / 2.0;
+                    level.terrain.push(Terrain {
+                        position: to_level(centre.x, centre.y),
+                        shape: TerrainShape::Rect { half_extents: size / 2.0 },
+                        friction: 0.5,
+                        restitution: 0.5,
+                    });
+                }
+            }
+            TiledLayer::ObjectGroup { objects } =>
+            {
+                for object in objects
+                {
+                    let top = if object.gid.is_some() { object.y - object.height } else { object.y };
+                    let position = to_level(object.x + object.width / 2.0, top + object.height / 2.0);
+                    add_object(&mut level, object, position)?;
+                }
+            }
+            TiledLayer::Group { .. } | TiledLayer::Other => {}
+        }
+    }
+    Ok(level)
+}
+
+/// The layers in the order Tiled draws them, with groups expanded.
+fn flatten<'a>(layers: &'a [TiledLayer], out: &mut Vec<&'a TiledLayer>)
+{
+    for layer in layers
+    {
+        match layer
+        {
+            TiledLayer::Group { layers } => flatten(layers, out),
+            layer => out.push(layer),
+        }
+    }
+}
+
+fn add_object(level: &mut Level, object: &TiledObject, position: Vec2) -> Result<(), TiledError>
+{
+    let mut fields: Map<String, Value> = object.properties.iter().map(|p| (p.name.clone(), p.value.clone())).collect();
+    fields.insert("position".into(), serde_json::json!([position.x, position.y]));
+    if object.class == "BombPlaceSpot" && !fields.contains_key("radius")
+    {
+        fields.insert("radius".into(), serde_json::json!(object.width.max(object.height) / 2.0));
+    }
+    let fields = Value::Object(fields);
+    let error = |source| TiledError::Object { name: object.name.clone(), class: object.class.clone(), source };
+
+    match object.class.as_str()
+    {
+        "Player" => level.players.push(PlayerSpawn::deserialize(fields).map_err(error)?),
+        "Enemy" => level.enemies.push(EnemySpawn::deserialize(fields).map_err(error)?),
+        "Ball" => level.balls.push(BallSpawn::deserialize(fields).map_err(error)?),
+        "BombPlaceSpot" => level.place_spots.push(PlaceSpotSpawn::deserialize(fields).map_err(error)?),
+        "BombPickup" => level.pickups.push(PickupSpawn::deserialize(fields).map_err(error)?),
+        class => println!("Ignoring Tiled object {:?} of unknown class {:?}", object.name, class),
+    }
+    Ok(())
+}
+
+/// Covers the `solid` tiles (row by row, `width` per row) with few rectangles, in tile coordinates.
+///
+/// Greedy: each rectangle starts at the first uncovered solid tile, grows right as far as it can,
+/// then down for as long as the whole row below is solid and uncovered.
+pub fn merge_tiles(solid: &[bool], width: usize, height: usize) -> Vec<URect>
+{
+    let mut covered = vec![false; solid.len()];
+    let free = |covered: &[bool], x: usize, y: usize| solid[y * width + x] && !covered[y * width + x];
+    let mut rects = Vec::new();
+
+    for y in 0..height
+    {
+        for x in 0..width
+        {
+            if !free(&covered, x, y) { continue; }
+
+            let mut right = x + 1;
+            while right < width && free(&covered, right, y) { right += 1; }
+            let mut bottom = y + 1;
+            while bottom < height && (x..right).all(|x| free(&covered, x, bottom)) { bottom += 1; }
+
+            for row in y..bottom
+            {
+                covered[row * width + x..row * width + right].fill(true);
+            }
+            rects.push(URect::new(x as u32, y as u32, right as u32, bottom as u32));
+        }
+    }
+    rects
+}
+
+#[derive(Default)]
+pub struct TiledLoader;
+
+impl AssetLoader for TiledLoader
+{
+    type Asset = Level;
+    type Settings = ();
+    type Error = TiledError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error>
+    {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        level_from_tiled(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str]
+    {
+        &["tmj"]
+    }
+}
@@ -0,0 +1,305 @@
+mod common;
+
+use bevy::prelude::*;
+
+use sticky_bomb::aseprite::{parse_aseprite, AnimationDirection, Aseprite, AsepriteAnimation, AsepriteFrame, AsepriteTag};
+use sticky_bomb::character::{MovementState, Pose};
+
+use common::*;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+/// A 32-bit RGBA file of `size` with the given frames, each already a list of chunks.
+fn aseprite_file(size: (u16, u16), frames: &[Vec<u8>]) -> Vec<u8>
+{
+    let mut bytes = Vec::new();
+    bytes.extend(0u32.to_le_bytes());
+    bytes.extend(0xA5E0u16.to_le_bytes());
+    bytes.extend((frames.len() as u16).to_le_bytes());
+    bytes.extend(size.0.to_le_bytes());
+    bytes.extend(size.1.to_le_bytes());
+    bytes.extend(32u16.to_le_bytes());
+    // Layer opacity is valid
+    bytes.extend(1u32.to_le_bytes());
+    bytes.resize(128, 0);
+    for frame in frames { bytes.extend(frame); }
+    let len = bytes.len() as u32;
+    bytes[..4].copy_from_slice(&len.to_le_bytes());
+    bytes
+}
+
+fn frame(duration_ms: u16, chunks: &[Vec<u8>]) -> Vec<u8>
+{
+    let body: Vec<u8> = chunks.concat();
+    let mut bytes = Vec::new();
+    bytes.extend((16 + body.len() as u32).to_le_bytes());
+    bytes.extend(0xF1FAu16.to_le_bytes());
+    bytes.extend((chunks.len() as u16).to_le_bytes());
+    bytes.extend(duration_ms.to_le_bytes());
+    bytes.extend([0; 2]);
+    bytes.extend((chunks.len() as u32).to_le_bytes());
+    bytes.extend(body);
+    bytes
+}
+
+fn chunk(chunk_type: u16, data: Vec<u8>) -> Vec<u8>
+{
+    let mut bytes = Vec::new();
+    bytes.extend((6 + data.len() as u32).to_le_bytes());
+    bytes.extend(chunk_type.to_le_bytes());
+    bytes.extend(data);
+    bytes
+}
+
+fn string(s: &str) -> Vec<u8>
+{
+    [(s.len() as u16).to_le_bytes().as_slice(), s.as_bytes()].concat()
+}
+
+fn layer(name: &str, visible: bool, opacity: u8) -> Vec<u8>
+{
+    let mut data = Vec::new();
+    data.extend((visible as u16).to_le_bytes());
+    // Image layer, at the top level, with a normal blend mode
+    data.extend([0; 10]);
+    data.push(opacity);
+    data.extend([0; 3]);
+    data.extend(string(name));
+    chunk(0x2004, data)
+}
+
+fn cel_header(layer: u16, position: (i16, i16), cel_type: u16) -> Vec<u8>
+{
+    let mut data = Vec::new();
+    data.extend(layer.to_le_bytes());
+    data.extend(position.0.to_le_bytes());
+    data.extend(position.1.to_le_bytes());
+    data.push(255);
+    data.extend(cel_type.to_le_bytes());
+    data.extend([0; 7]);
+    data
+}
+
+fn raw_cel(layer: u16, position: (i16, i16), size: (u16, u16), pixels: &[[u8; 4]]) -> Vec<u8>
+{
+    let mut data = cel_header(layer, position, 0);
+    data.extend(size.0.to_le_bytes());
+    data.extend(size.1.to_le_bytes());
+    data.extend(pixels.concat());
+    chunk(0x2005, data)
+}
+
+fn linked_cel(layer: u16, frame: u16) -> Vec<u8>
+{
+    let mut data = cel_header(layer, (0, 0), 1);
+    data.extend(frame.to_le_bytes());
+    chunk(0x2005, data)
+}
+
+fn tags(tags: &[(&str, u16, u16, u8)]) -> Vec<u8>
+{
+    let mut data = Vec::new();
+    data.extend((tags.len() as u16).to_le_bytes());
+    data.extend([0; 8]);
+    for &(name, from, to, direction) in tags
+    {
+        data.extend(from.to_le_bytes());
+        data.extend(to.to_le_bytes());
+        data.push(direction);
+        data.extend(0u16.to_le_bytes());
+        data.extend([0; 10]);
+        data.extend(string(name));
+    }
+    chunk(0x2018, data)
+}
+
+fn pixel(pixels: &[u8], width: usize, x: usize, y: usize) -> [u8; 4]
+{
+    let i = (y * width + x) * 4;
+    pixels[i..i + 4].try_into().unwrap()
+}
+
+#[test]
+fn shipped_guy_parses()
+{
+    let data = parse_aseprite(include_bytes!("../assets/guy.aseprite")).unwrap();
+    assert_eq!(data.size, UVec2::new(16, 16));
+    assert_eq!(data.frames.len(), 1);
+    assert_eq!(data.frames[0].duration, 0.1);
+    assert_eq!(data.frames[0].pixels.len(), 16 * 16 * 4);
+    assert_eq!(pixel(&data.frames[0].pixels, 16, 0, 0)[3], 0, "the corner is outside the cel");
+    assert!(data.frames[0].pixels.chunks(4).any(|color| color[3] == 255));
+}
+
+#[test]
+fn frames_flatten_visible_layers_and_follow_links()
+{
+    let bytes = aseprite_file((2, 2), &[
+        frame(100, &[
+            layer("back", true, 255),
+            layer("hidden", false, 255),
+            layer("front", true, 255),
+            raw_cel(0, (0, 0), (2, 1), &[RED, RED]),
+            raw_cel(1, (0, 0), (2, 2), &[BLUE; 4]),
+            raw_cel(2, (1, 0), (1, 2), &[BLUE, BLUE]),
+        ]),
+        frame(250, &[linked_cel(0, 0)]),
+        frame(50, &[tags(&[("run", 0, 1, 0), ("fall", 2, 2, 0)])]),
+    ]);
+    let data = parse_aseprite(&bytes).unwrap();
+
+    assert_eq!(data.size, UVec2::new(2, 2));
+    assert_eq!(data.frames.iter().map(|frame| frame.duration).collect::<Vec<_>>(), vec![0.1, 0.25, 0.05]);
+
+    let first = &data.frames[0].pixels;
+    assert_eq!(pixel(first, 2, 0, 0), RED);
+    assert_eq!(pixel(first, 2, 1, 0), BLUE, "the front layer covers the back");
+    assert_eq!(pixel(first, 2, 0, 1), [0; 4], "the hidden layer isn't drawn");
+    assert_eq!(pixel(first, 2, 1, 1), BLUE);
+
+    let linked = &data.frames[1].pixels;
+    assert_eq!(pixel(linked, 2, 0, 0), RED);
+    assert_eq!(pixel(linked, 2, 1, 0), RED, "only the back layer is linked");
+    assert!(data.frames[2].pixels.iter().all(|&byte| byte == 0));
+
+    assert_eq!(data.tags, vec![
+        AsepriteTag { name: "run".into(), from: 0, to: 1, direction: AnimationDirection::Forward, repeat: 0 },
+        AsepriteTag { name: "fall".into(), from: 2, to: 2, direction: AnimationDirection::Forward, repeat: 0 },
+    ]);
+}
+
+#[test]
+fn layer_opacity_blends_over_what_is_below()
+{
+    let bytes = aseprite_file((1, 1), &[frame(100, &[
+        layer("back", true, 255),
+        layer("front", true, 128),
+        raw_cel(0, (0, 0), (1, 1), &[RED]),
+        raw_cel(1, (0, 0), (1, 1), &[BLUE]),
+    ])]);
+    let data = parse_aseprite(&bytes).unwrap();
+    assert_eq!(pixel(&data.frames[0].pixels, 1, 0, 0), [127, 0, 128, 255]);
+}
+
+#[test]
+fn truncated_files_are_rejected()
+{
+    let bytes = aseprite_file((1, 1), &[frame(100, &[layer("back", true, 255)])]);
+    assert!(parse_aseprite(&bytes[..bytes.len() - 3]).is_err());
+    assert!(parse_aseprite(b"not an aseprite file at all").is_err());
+}
+
+/// Frames of `durations` seconds, without images.
+fn animation(durations: &[f32], tags: Vec<AsepriteTag>) -> Aseprite
+{
+    Aseprite {
+        frames: durations.iter().map(|&duration| AsepriteFrame { image: Handle::default(), duration }).collect(),
+        tags,
+    }
+}
+
+fn tag(name: &str, from: usize, to: usize, direction: AnimationDirection, repeat: u16) -> AsepriteTag
+{
+    AsepriteTag { name: name.into(), from, to, direction, repeat }
+}
+
+/// The frames shown over `steps` advances of 0.1 seconds, after starting.
+fn play(aseprite: &Aseprite, animation: &mut AsepriteAnimation, steps: usize) -> Vec<usize>
+{
+    animation.advance(aseprite, 0.0);
+    (0..steps)
+        .map(|_| {
+            animation.advance(aseprite, 0.1);
+            animation.frame().unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn animations_loop_through_their_tag()
+{
+    let aseprite = animation(&[0.1; 6], vec![tag("run", 1, 3, AnimationDirection::Forward, 0)]);
+    let mut animation = AsepriteAnimation::default();
+
+    assert_eq!(play(&aseprite, &mut animation, 7), vec![1, 2, 3, 4, 5, 0, 1], "every frame without a tag");
+
+    animation.play("run");
+    assert_eq!(play(&aseprite, &mut animation, 5), vec![2, 3, 1, 2, 3]);
+}
+
+#[test]
+fn animations_follow_the_tag_direction_and_repeat()
+{
+    let aseprite = animation(&[0.1; 4], vec![
+        tag("back", 0, 2, AnimationDirection::Reverse, 0),
+        tag("bounce", 0, 2, AnimationDirection::PingPong, 0),
+        tag("twice", 1, 2, AnimationDirection::Forward, 2),
+    ]);
+    let mut animation = AsepriteAnimation::default();
+
+    animation.play("back");
+    assert_eq!(play(&aseprite, &mut animation, 4), vec![1, 0, 2, 1]);
+
+    animation.play("bounce");
+    assert_eq!(play(&aseprite, &mut animation, 6), vec![1, 2, 1, 0, 1, 2]);
+
+    animation.play("twice");
+    assert_eq!(play(&aseprite, &mut animation, 5), vec![2, 1, 2, 2, 2], "stops on the last frame");
+}
+
+#[test]
+fn long_frames_hold_and_short_ones_are_skipped()
+{
+    let aseprite = animation(&[0.25, 0.05, 0.1], vec![]);
+    let mut animation = AsepriteAnimation::default();
+    assert_eq!(play(&aseprite, &mut animation, 5), vec![0, 0, 2, 0, 0]);
+}
+
+#[test]
+fn aseprite_files_load_through_the_asset_server()
+{
+    let mut app = headless_app();
+    let handle: Handle<Aseprite> = app.world().resource::<AssetServer>().load("guy.aseprite");
+    load(&mut app, &handle);
+
+    let aseprite = app.world().resource::<Assets<Aseprite>>().get(&handle).unwrap();
+    assert_eq!(aseprite.frames.len(), 1);
+    let image = app.world().resource::<Assets<Image>>().get(&aseprite.frames[0].image).unwrap();
+    assert_eq!(image.size(), UVec2::new(16, 16));
+}
+
+#[test]
+fn characters_animate_their_pose()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let aseprite = animation(&[0.1; 3], vec![
+        tag("idle", 0, 0, AnimationDirection::Forward, 0),
+        tag("run", 1, 1, AnimationDirection::Forward, 0),
+        tag("jump", 2, 2, AnimationDirection::Forward, 0),
+    ]);
+    let handle = app.world_mut().resource_mut::<Assets<Aseprite>>().add(aseprite);
+    let player = spawn_player(&mut app, Vec2::new(0.0, 8.0));
+    app.world_mut().entity_mut(player).insert(AsepriteAnimation::new(handle));
+    step(&mut app, 30);
+
+    let pose = |app: &App| app.world().get::<MovementState>(player).unwrap().pose;
+    let tag = |app: &App| app.world().get::<AsepriteAnimation>(player).unwrap().tag().map(str::to_owned);
+    assert_eq!(pose(&app), Pose::Idle);
+    assert_eq!(tag(&app).as_deref(), Some("idle"));
+
+    press(&mut app, KeyCode::ArrowRight);
+    step(&mut app, 10);
+    assert_eq!(pose(&app), Pose::Run);
+    assert_eq!(tag(&app).as_deref(), Some("run"));
+
+    press(&mut app, KeyCode::ArrowUp);
+    step(&mut app, 5);
+    assert_eq!(pose(&app), Pose::Jump);
+    assert_eq!(tag(&app).as_deref(), Some("jump"));
+
+    release(&mut app, KeyCode::ArrowUp);
+    step(&mut app, 60);
+    assert_eq!(pose(&app), Pose::Run, "landed again");
+}
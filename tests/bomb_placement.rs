@@ -0,0 +1,110 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use sticky_bomb::bomb::{Bomb, BombDetonated, SelectedPlaceSpot};
+
+use common::*;
+
+fn bombs(app: &mut App) -> Vec<(Entity, Entity)>
+{
+    app.world_mut()
+        .query_filtered::<(Entity, &ChildOf), With<Bomb>>()
+        .iter(app.world())
+        .map(|(bomb, child_of)| (bomb, child_of.parent()))
+        .collect()
+}
+
+fn selected_spot(app: &App, player: Entity) -> Option<Entity>
+{
+    let placer = app.world().get::<Children>(player).unwrap()[0];
+    app.world().get::<SelectedPlaceSpot>(placer).unwrap().0
+}
+
+#[test]
+fn bomb_is_placed_on_the_spot_in_range_and_detonates()
+{
+    let mut app = headless_app();
+    collect::<BombDetonated>(&mut app);
+    spawn_ground(&mut app);
+    let (_, spot) = spawn_place_spot(&mut app, Vec2::new(20.0, 4.0));
+    let player = spawn_player(&mut app, Vec2::new(0.0, 10.0));
+    step(&mut app, 10);
+
+    assert_eq!(selected_spot(&app, player), Some(spot));
+
+    press(&mut app, KeyCode::ArrowDown);
+    step(&mut app, 1);
+    let placed = bombs(&mut app);
+    assert_eq!(placed.len(), 1);
+    assert_eq!(placed[0].1, spot);
+
+    // Pressing again doesn't stack a second bomb on the same spot.
+    release(&mut app, KeyCode::ArrowDown);
+    step(&mut app, 1);
+    press(&mut app, KeyCode::ArrowDown);
+    step(&mut app, 1);
+    assert_eq!(bombs(&mut app).len(), 1);
+
+    step(&mut app, 180);
+    assert!(bombs(&mut app).is_empty());
+    let detonations = collected::<BombDetonated>(&app);
+    assert_eq!(detonations.len(), 1);
+    assert_eq!(detonations[0].bomb, placed[0].0);
+    assert!(detonations[0].position.distance(Vec2::new(20.0, 4.0)) < 0.01);
+}
+
+#[test]
+fn nothing_is_placed_out_of_range()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    spawn_place_spot(&mut app, Vec2::new(200.0, 4.0));
+    let player = spawn_player(&mut app, Vec2::new(0.0, 10.0));
+    step(&mut app, 10);
+
+    assert_eq!(selected_spot(&app, player), None);
+    press(&mut app, KeyCode::ArrowDown);
+    step(&mut app, 1);
+    assert!(bombs(&mut app).is_empty());
+}
+
+#[test]
+fn nearest_spot_is_selected_and_survives_leaving_another()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let (_, near) = spawn_place_spot(&mut app, Vec2::new(10.0, 4.0));
+    let (far_post, _) = spawn_place_spot(&mut app, Vec2::new(-30.0, 4.0));
+    let player = spawn_player(&mut app, Vec2::new(0.0, 10.0));
+    step(&mut app, 10);
+
+    assert_eq!(selected_spot(&app, player), Some(near));
+
+    app.world_mut().entity_mut(far_post).despawn();
+    step(&mut app, 2);
+    assert_eq!(selected_spot(&app, player), Some(near));
+}
+
+#[test]
+fn detonation_pushes_nearby_dynamic_bodies_away()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    spawn_place_spot(&mut app, Vec2::new(20.0, 4.0));
+    spawn_player(&mut app, Vec2::new(0.0, 10.0));
+    let ball = app.world_mut().spawn((
+        RigidBody::Dynamic,
+        Collider::ball(6.0),
+        Velocity::default(),
+        Transform::from_xyz(50.0, 6.0, 0.0),
+    )).id();
+    step(&mut app, 10);
+
+    press(&mut app, KeyCode::ArrowDown);
+    step(&mut app, 185);
+
+    let velocity = app.world().get::<Velocity>(ball).unwrap().linvel;
+    assert!(velocity.x > 10.0, "ball is pushed away from the bomb, got {velocity}");
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use std::borrow::Cow;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use sticky_bomb::bomb::{armed_bomb, flying_bomb, Bomb, BombDetonated, BombKind, Fuse};
+use sticky_bomb::bomb_type::{BombType, BombTypeAppExt, BombTypes, Frozen, MineBomb};
+use sticky_bomb::physics::{collision_groups, GROUP_PROP};
+use sticky_bomb::sticky::StuckTo;
+
+use common::*;
+
+fn detonate(app: &mut App, kind: BombKind, position: Vec2)
+{
+    app.world_mut().send_event(BombDetonated { bomb: Entity::PLACEHOLDER, kind, position });
+}
+
+fn spawn_ball(app: &mut App, position: Vec2) -> Entity
+{
+    app.world_mut().spawn((
+        RigidBody::Dynamic,
+        Collider::ball(6.0),
+        collision_groups(GROUP_PROP),
+        Velocity::default(),
+        Transform::from_translation(position.extend(0.0)),
+    )).id()
+}
+
+fn bombs_of(app: &mut App, kind: &BombKind) -> usize
+{
+    app.world_mut().query::<(&Bomb, &BombKind)>().iter(app.world()).filter(|(_, k)| *k == kind).count()
+}
+
+#[test]
+fn cluster_bomb_bursts_into_shards()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    step(&mut app, 1);
+
+    detonate(&mut app, BombKind::CLUSTER, Vec2::new(0.0, 4.0));
+    step(&mut app, 1);
+    assert_eq!(bombs_of(&mut app, &BombKind::CLUSTER_SHARD), 5);
+
+    // The shards go off on their own shortly after.
+    step(&mut app, 120);
+    assert_eq!(bombs_of(&mut app, &BombKind::CLUSTER_SHARD), 0);
+}
+
+#[test]
+fn mine_fuse_only_burns_once_stuck()
+{
+    let mut app = headless_app();
+    let mine = app.world_mut().spawn((armed_bomb(BombKind::MINE, &MineBomb::default()), flying_bomb(Vec2::new(0.0, 400.0), Vec2::ZERO))).id();
+    step(&mut app, 60);
+    assert_eq!(app.world().get::<Fuse>(mine).unwrap().0.elapsed_secs(), 0.0, "still flying");
+
+    spawn_ground(&mut app);
+    app.world_mut().get_mut::<Transform>(mine).unwrap().translation = Vec3::new(0.0, 10.0, 1.0);
+    app.world_mut().get_mut::<Velocity>(mine).unwrap().linvel = Vec2::new(0.0, -20.0);
+    step(&mut app, 30);
+    assert!(app.world().get::<StuckTo>(mine).is_some());
+    assert!(app.world().get::<Fuse>(mine).unwrap().0.elapsed_secs() > 0.0);
+
+    step(&mut app, 300);
+    assert!(app.world().get_entity(mine).is_err(), "went off after its fuse");
+}
+
+#[test]
+fn gravity_well_pulls_bodies_in()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let ball = spawn_ball(&mut app, Vec2::new(60.0, 6.0));
+    step(&mut app, 10);
+
+    detonate(&mut app, BombKind::GRAVITY_WELL, Vec2::new(0.0, 6.0));
+    step(&mut app, 60);
+    assert!(translation(&app, ball).x < 50.0, "ball is pulled towards the well, at {:?}", translation(&app, ball));
+}
+
+#[test]
+fn freeze_holds_bodies_still_then_lets_go()
+{
+    let mut app = headless_app();
+    let ball = spawn_ball(&mut app, Vec2::new(0.0, 100.0));
+    step(&mut app, 10);
+
+    detonate(&mut app, BombKind::FREEZE, Vec2::new(0.0, 100.0));
+    step(&mut app, 1);
+    assert!(app.world().get::<Frozen>(ball).is_some());
+    let frozen_at = translation(&app, ball);
+    step(&mut app, 60);
+    assert_eq!(translation(&app, ball), frozen_at);
+    assert_eq!(*app.world().get::<RigidBody>(ball).unwrap(), RigidBody::Fixed);
+
+    step(&mut app, 180);
+    assert_eq!(*app.world().get::<RigidBody>(ball).unwrap(), RigidBody::Dynamic);
+    assert!(app.world().get::<Frozen>(ball).is_none());
+    assert!(translation(&app, ball).y < frozen_at.y - 1.0, "falls again once thawed");
+}
+
+#[derive(Component)]
+struct Fizzled;
+
+/// Does nothing but leave a [`Fizzled`] behind.
+struct Dud;
+
+impl BombType for Dud
+{
+    fn fuse_secs(&self) -> f32 { 0.5 }
+    fn radius(&self) -> f32 { 0.0 }
+    fn color(&self) -> Color { Color::WHITE }
+
+    fn detonate(&self, _detonation: &BombDetonated, _types: &BombTypes, commands: &mut Commands)
+    {
+        commands.spawn(Fizzled);
+    }
+}
+
+#[test]
+fn new_types_can_be_registered()
+{
+    const DUD: BombKind = BombKind(Cow::Borrowed("dud"));
+    let mut app = headless_app();
+    app.register_bomb_type(DUD, Dud);
+
+    let dud = armed_bomb(DUD, app.world().resource::<BombTypes>().get(&DUD).unwrap());
+    let bomb = app.world_mut().spawn(dud).id();
+    step(&mut app, 40);
+
+    assert!(app.world().get_entity(bomb).is_err());
+    let fizzled = app.world_mut().query::<&Fizzled>().iter(app.world()).count();
+    assert_eq!(fizzled, 1);
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use bevy::prelude::*;
+
+use sticky_bomb::camera::CameraFollow;
+use sticky_bomb::character::Player;
+use sticky_bomb::level::LevelBounds;
+
+use common::*;
+
+/// A bare [`Player`] that only moves when told to, so the camera can be tested on its own.
+fn spawn_target(app: &mut App, position: Vec2) -> Entity
+{
+    app.world_mut().spawn((Player, Sprite::default(), Transform::from_translation(position.extend(0.0)))).id()
+}
+
+fn move_target(app: &mut App, target: Entity, position: Vec2)
+{
+    let mut transform = app.world_mut().get_mut::<Transform>(target).unwrap();
+    transform.translation = position.extend(0.0);
+}
+
+fn spawn_camera(app: &mut App, follow: CameraFollow) -> Entity
+{
+    app.world_mut().spawn(follow).id()
+}
+
+#[test]
+fn camera_jumps_ahead_of_a_new_player_on_whole_texels()
+{
+    let mut app = headless_app();
+    let camera = spawn_camera(&mut app, CameraFollow { look_ahead: 10.0, ..default() });
+    spawn_target(&mut app, Vec2::new(100.3, 50.6));
+    step(&mut app, 1);
+
+    assert_eq!(translation(&app, camera), Vec2::new(110.0, 51.0));
+}
+
+#[test]
+fn camera_stays_put_while_the_player_is_in_the_dead_zone()
+{
+    let mut app = headless_app();
+    let camera = spawn_camera(&mut app, CameraFollow { look_ahead: 0.0, dead_zone: Vec2::new(20.0, 20.0), ..default() });
+    let target = spawn_target(&mut app, Vec2::ZERO);
+    step(&mut app, 1);
+
+    move_target(&mut app, target, Vec2::new(15.0, -15.0));
+    step(&mut app, 30);
+    assert_eq!(translation(&app, camera), Vec2::ZERO);
+
+    move_target(&mut app, target, Vec2::new(100.0, 0.0));
+    step(&mut app, 120);
+    assert_eq!(translation(&app, camera), Vec2::new(80.0, 0.0), "the player is back on the edge of the dead zone");
+}
+
+#[test]
+fn camera_eases_towards_the_player()
+{
+    let mut app = headless_app();
+    let camera = spawn_camera(&mut app, CameraFollow { look_ahead: 0.0, dead_zone: Vec2::ZERO, smoothing: 0.2, ..default() });
+    let target = spawn_target(&mut app, Vec2::ZERO);
+    step(&mut app, 1);
+
+    move_target(&mut app, target, Vec2::new(100.0, 0.0));
+    let mut xs = Vec::new();
+    for _ in 0..120
+    {
+        step(&mut app, 1);
+        xs.push(translation(&app, camera).x);
+    }
+
+    assert!(xs[0] > 0.0 && xs[0] < 20.0, "the first step only covers part of the way: {}", xs[0]);
+    assert!(xs.windows(2).all(|pair| pair[1] >= pair[0]));
+    assert_eq!(xs[119], 100.0);
+}
+
+#[test]
+fn look_ahead_follows_the_facing_direction()
+{
+    let mut app = headless_app();
+    let camera = spawn_camera(&mut app, CameraFollow { look_ahead: 40.0, dead_zone: Vec2::new(8.0, 8.0), ..default() });
+    let target = spawn_target(&mut app, Vec2::ZERO);
+    step(&mut app, 1);
+    assert_eq!(translation(&app, camera).x, 40.0);
+
+    app.world_mut().get_mut::<Sprite>(target).unwrap().flip_x = true;
+    step(&mut app, 120);
+    assert_eq!(translation(&app, camera).x, -32.0);
+}
+
+#[test]
+fn camera_keeps_the_view_within_the_level_bounds()
+{
+    let mut app = headless_app();
+    app.insert_resource(LevelBounds(Rect::new(-400.0, -100.0, 400.0, 200.0)));
+    let camera = spawn_camera(&mut app, CameraFollow { view: Vec2::new(200.0, 400.0), ..default() });
+    let target = spawn_target(&mut app, Vec2::new(390.0, 0.0));
+    step(&mut app, 1);
+
+    assert_eq!(translation(&app, camera), Vec2::new(300.0, 50.0), "against the right edge, centred vertically");
+
+    move_target(&mut app, target, Vec2::new(-1000.0, 0.0));
+    step(&mut app, 120);
+    assert_eq!(translation(&app, camera).x, -300.0);
+}
+
+#[test]
+fn texel_offset_is_what_snapping_left_over()
+{
+    let mut app = headless_app();
+    let camera = spawn_camera(&mut app, CameraFollow { look_ahead: 0.0, dead_zone: Vec2::ZERO, ..default() });
+    let target = spawn_target(&mut app, Vec2::ZERO);
+    step(&mut app, 1);
+
+    move_target(&mut app, target, Vec2::new(37.0, -11.0));
+    for _ in 0..30
+    {
+        step(&mut app, 1);
+        let follow = app.world().get::<CameraFollow>(camera).unwrap();
+        let offset = follow.texel_offset();
+        assert!(offset.abs().max_element() <= 0.5, "{offset}");
+        assert_eq!(translation(&app, camera) + offset, follow.position);
+    }
+}
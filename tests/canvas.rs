@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use bevy::render::camera::ScalingMode;
+use bevy::window::WindowResolution;
+
+use sticky_bomb::camera::CameraFollow;
+use sticky_bomb::graphics::{canvas_to_world, CanvasScaling};
+
+const RESOLUTION: Vec2 = Vec2::new(640.0, 360.0);
+
+/// A window `logical` pixels big, at `scale_factor` physical pixels to each logical one.
+fn window(logical: (f32, f32), scale_factor: f32) -> Window
+{
+    let resolution = WindowResolution::new(logical.0 * scale_factor, logical.1 * scale_factor)
+        .with_scale_factor_override(scale_factor);
+    Window { resolution, ..default() }
+}
+
+#[test]
+fn integer_scaling_never_overflows_the_window()
+{
+    let projection = CanvasScaling::Integer.projection(RESOLUTION, &window((1900.0, 1070.0), 1.0));
+    assert_eq!(projection.scale, 1.0 / 2.0, "1900x1070 is just short of 3x");
+    assert!(matches!(projection.scaling_mode, ScalingMode::WindowSize));
+
+    let projection = CanvasScaling::Integer.projection(RESOLUTION, &window((1920.0, 1080.0), 1.0));
+    assert_eq!(projection.scale, 1.0 / 3.0);
+}
+
+#[test]
+fn integer_scaling_is_measured_in_logical_pixels()
+{
+    let hidpi = window((1280.0, 720.0), 2.0);
+    let projection = CanvasScaling::Integer.projection(RESOLUTION, &hidpi);
+    assert_eq!(projection.scale, 1.0 / 2.0, "twice the resolution in logical pixels, four times in physical ones");
+
+    let viewport = CanvasScaling::Integer.viewport(RESOLUTION, &hidpi).unwrap();
+    assert_eq!(viewport.physical_size, UVec2::new(2560, 1440));
+    assert_eq!(viewport.physical_position, UVec2::ZERO);
+}
+
+#[test]
+fn letterboxed_modes_crop_to_the_resolution()
+{
+    let window = window((1366.0, 768.0), 1.0);
+
+    let viewport = CanvasScaling::Integer.viewport(RESOLUTION, &window).unwrap();
+    assert_eq!(viewport.physical_size, UVec2::new(1280, 720), "the margin isn't shown");
+    assert_eq!(viewport.physical_position, UVec2::new(43, 24));
+
+    let viewport = CanvasScaling::Fit.viewport(RESOLUTION, &window).unwrap();
+    assert_eq!(viewport.physical_size, UVec2::new(1365, 768));
+    assert_eq!(viewport.physical_position, UVec2::ZERO);
+
+    assert!(CanvasScaling::Stretch.viewport(RESOLUTION, &window).is_none());
+    assert!(CanvasScaling::Crop.viewport(RESOLUTION, &window).is_none());
+}
+
+#[test]
+fn integer_scaling_is_at_least_one()
+{
+    let projection = CanvasScaling::Integer.projection(RESOLUTION, &window((320.0, 180.0), 1.0));
+    assert_eq!(projection.scale, 1.0);
+}
+
+#[test]
+fn other_modes_show_the_resolution_in_their_own_way()
+{
+    let window = &window((1000.0, 1000.0), 1.0);
+    assert!(matches!(
+        CanvasScaling::Fit.projection(RESOLUTION, window).scaling_mode,
+        ScalingMode::AutoMin { min_width: 640.0, min_height: 360.0 },
+    ));
+    assert!(matches!(
+        CanvasScaling::Stretch.projection(RESOLUTION, window).scaling_mode,
+        ScalingMode::Fixed { width: 640.0, height: 360.0 },
+    ));
+    assert!(matches!(
+        CanvasScaling::Crop.projection(RESOLUTION, window).scaling_mode,
+        ScalingMode::AutoMax { max_width: 640.0, max_height: 360.0 },
+    ));
+    for mode in [CanvasScaling::Fit, CanvasScaling::Stretch, CanvasScaling::Crop]
+    {
+        assert_eq!(mode.projection(RESOLUTION, window).scale, 1.0, "{mode:?}");
+    }
+}
+
+fn at(x: f32, y: f32) -> GlobalTransform
+{
+    GlobalTransform::from_xyz(x, y, 0.0)
+}
+
+#[test]
+fn canvas_centre_is_where_the_camera_is()
+{
+    assert_eq!(canvas_to_world(Vec2::ZERO, RESOLUTION, &at(0.0, 0.0), &at(120.0, -40.0)), Some(Vec2::new(120.0, -40.0)));
+}
+
+#[test]
+fn canvas_edges_are_half_the_resolution_away()
+{
+    let camera = at(120.0, -40.0);
+    let canvas = at(0.0, 0.0);
+    assert_eq!(canvas_to_world(Vec2::new(320.0, 180.0), RESOLUTION, &canvas, &camera), Some(Vec2::new(440.0, 140.0)), "top right");
+    assert_eq!(canvas_to_world(Vec2::new(-320.0, -180.0), RESOLUTION, &canvas, &camera), Some(Vec2::new(-200.0, -220.0)), "bottom left");
+}
+
+#[test]
+fn letterbox_is_outside_the_world()
+{
+    let camera = at(0.0, 0.0);
+    let canvas = at(0.0, 0.0);
+    assert_eq!(canvas_to_world(Vec2::new(320.5, 0.0), RESOLUTION, &canvas, &camera), None);
+    assert_eq!(canvas_to_world(Vec2::new(0.0, -181.0), RESOLUTION, &canvas, &camera), None);
+}
+
+#[test]
+fn scrolled_canvas_maps_to_the_exact_world_point()
+{
+    let follow = CameraFollow { position: Vec2::new(10.25, -4.75), ..default() };
+    assert_eq!(follow.texel_offset(), Vec2::new(0.25, 0.25));
+
+    // As `follow_player` and `scroll_canvas` leave them.
+    let camera = at(10.0, -5.0);
+    let canvas = at(-0.25, -0.25);
+
+    // What's on screen is where the unsnapped camera would have shown it.
+    let world = Vec2::new(50.5, 20.0);
+    let on_screen = world - follow.position;
+    assert_eq!(canvas_to_world(on_screen, RESOLUTION, &canvas, &camera), Some(world));
+}
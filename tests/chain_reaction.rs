@@ -0,0 +1,90 @@
+mod common;
+
+use bevy::prelude::*;
+
+use sticky_bomb::bomb::{armed_bomb, BombDetonated, BombKind};
+use sticky_bomb::bomb_type::StandardBomb;
+use sticky_bomb::chain_reaction::{ChainReactions, MAX_CHAIN_DETONATIONS_PER_TICK};
+
+use common::*;
+
+fn spawn_bomb(app: &mut App, position: Vec2) -> Entity
+{
+    app.world_mut().spawn((armed_bomb(BombKind::STANDARD, &StandardBomb::default()), Transform::from_translation(position.extend(0.0)))).id()
+}
+
+/// Sets off a standard bomb at `position`, as if one had been there.
+fn explode(app: &mut App, position: Vec2)
+{
+    app.world_mut().send_event(BombDetonated { bomb: Entity::PLACEHOLDER, kind: BombKind::STANDARD, position });
+}
+
+fn detonated(app: &App) -> Vec<Entity>
+{
+    collected::<BombDetonated>(app).iter().map(|d| d.bomb).filter(|&bomb| bomb != Entity::PLACEHOLDER).collect()
+}
+
+#[test]
+fn explosions_set_off_a_row_of_bombs_one_after_another()
+{
+    let mut app = headless_app();
+    collect::<BombDetonated>(&mut app);
+    let row: Vec<Entity> = (0..4).map(|i| spawn_bomb(&mut app, Vec2::new(40.0 * i as f32, 0.0))).collect();
+    step(&mut app, 1);
+
+    explode(&mut app, Vec2::new(-40.0, 0.0));
+    let mut went_off = Vec::new();
+    for tick in 0..120
+    {
+        let before = detonated(&app).len();
+        step(&mut app, 1);
+        if detonated(&app).len() > before { went_off.push(tick); }
+    }
+
+    assert_eq!(detonated(&app), row, "each bomb sets off the next one along");
+    assert!(went_off.windows(2).all(|pair| pair[1] - pair[0] >= 9), "with a delay in between, at ticks {went_off:?}");
+}
+
+#[test]
+fn caught_bombs_go_off_nearest_first_then_by_entity()
+{
+    let mut app = headless_app();
+    collect::<BombDetonated>(&mut app);
+    let far = spawn_bomb(&mut app, Vec2::new(30.0, 0.0));
+    let near = spawn_bomb(&mut app, Vec2::new(10.0, 0.0));
+    let middle_right = spawn_bomb(&mut app, Vec2::new(20.0, 0.0));
+    let middle_left = spawn_bomb(&mut app, Vec2::new(-20.0, 0.0));
+    step(&mut app, 1);
+
+    explode(&mut app, Vec2::ZERO);
+    step(&mut app, 30);
+
+    let mut middle = [middle_right, middle_left];
+    middle.sort();
+    assert_eq!(detonated(&app), vec![near, middle[0], middle[1], far]);
+}
+
+#[test]
+fn chain_detonations_are_capped_per_tick()
+{
+    let mut app = headless_app();
+    collect::<BombDetonated>(&mut app);
+    for i in 0..20
+    {
+        spawn_bomb(&mut app, Vec2::new(i as f32, 0.0));
+    }
+    step(&mut app, 1);
+
+    explode(&mut app, Vec2::ZERO);
+    let mut per_tick = Vec::new();
+    while detonated(&app).len() < 20 && per_tick.len() < 60
+    {
+        let before = detonated(&app).len();
+        step(&mut app, 1);
+        per_tick.push(detonated(&app).len() - before);
+    }
+
+    assert_eq!(detonated(&app).len(), 20);
+    assert!(per_tick.iter().all(|&n| n <= MAX_CHAIN_DETONATIONS_PER_TICK), "{per_tick:?}");
+    assert!(app.world().resource::<ChainReactions>().is_empty());
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use sticky_bomb::physics::*;
+use sticky_bomb::sensor::SensorEvent;
+
+use common::*;
+
+const GROUPS: [Group; 9] = [
+    GROUP_NORMAL_TERRAIN, GROUP_PLAYER, GROUP_ENEMY, GROUP_BOMB, GROUP_DEBRIS, GROUP_PROP,
+    GROUP_PICKUP, GROUP_PROXIMITY_PLACE_SPOT, GROUP_PROXIMITY_PLACER,
+];
+
+fn falling_body(app: &mut App, memberships: Group, position: Vec2) -> Entity
+{
+    app.world_mut().spawn((
+        RigidBody::Dynamic,
+        Collider::ball(3.0),
+        collision_groups(memberships),
+        Transform::from_translation(position.extend(0.0)),
+    )).id()
+}
+
+#[test]
+fn matrix_is_symmetric()
+{
+    for a in GROUPS
+    {
+        for b in GROUPS
+        {
+            assert_eq!(
+                collision_filters(a).contains(b),
+                collision_filters(b).contains(a),
+                "{a:?} and {b:?} disagree on whether they collide",
+            );
+        }
+    }
+}
+
+#[test]
+fn bombs_pass_through_players_but_land_on_terrain()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let player = spawn_player(&mut app, Vec2::new(0.0, 10.0));
+    step(&mut app, 30);
+    let bomb = falling_body(&mut app, GROUP_BOMB, Vec2::new(0.0, 40.0));
+    step(&mut app, 120);
+
+    assert!((translation(&app, bomb).y - 3.0).abs() < 1.0, "the bomb fell through the player onto the ground");
+    assert!((translation(&app, player).y - 8.0).abs() < 1.0);
+}
+
+#[test]
+fn props_land_on_players()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    spawn_player(&mut app, Vec2::new(0.0, 10.0));
+    step(&mut app, 30);
+    let prop = falling_body(&mut app, GROUP_PROP, Vec2::new(0.0, 40.0));
+    step(&mut app, 120);
+
+    assert!(translation(&app, prop).y > 16.0, "the prop rests on the player");
+}
+
+#[test]
+fn debris_does_not_trigger_sensors()
+{
+    let mut app = headless_app();
+    collect::<SensorEvent>(&mut app);
+    spawn_ground(&mut app);
+    spawn_place_spot(&mut app, Vec2::new(100.0, 4.0));
+    step(&mut app, 5);
+    falling_body(&mut app, GROUP_DEBRIS, Vec2::new(100.0, 60.0));
+    step(&mut app, 120);
+
+    assert!(collected::<SensorEvent>(&app).is_empty());
+}
@@ -0,0 +1,149 @@
+//! Headless harness: runs the [`StickyBombsGamePlugin`] without a window, one fixed tick per update.
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier2d::prelude::*;
+
+use sticky_bomb::StickyBombsGamePlugin;
+use sticky_bomb::bomb::{BombPlaceSpotBundle, BombPlacerBundle};
+use sticky_bomb::character::{CharacterBundle, Enemy, Player};
+use sticky_bomb::enemy::EnemyState;
+use sticky_bomb::physics::GROUP_ENEMY;
+
+pub const TICK: Duration = sticky_bomb::TIMESTEP;
+
+pub fn headless_app() -> App
+{
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, TransformPlugin, AssetPlugin::default()))
+        .init_asset::<Image>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(TICK))
+        .add_plugins(StickyBombsGamePlugin);
+    app.finish();
+    app.cleanup();
+    // Time doesn't advance on the very first update, so get it out of the way.
+    app.update();
+    app
+}
+
+/// Runs `ticks` updates. Keys stay pressed, but only count as just pressed for the first one.
+pub fn step(app: &mut App, ticks: usize)
+{
+    for _ in 0..ticks
+    {
+        app.update();
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().clear();
+    }
+}
+
+pub fn press(app: &mut App, key: KeyCode)
+{
+    app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(key);
+}
+
+pub fn release(app: &mut App, key: KeyCode)
+{
+    app.world_mut().resource_mut::<ButtonInput<KeyCode>>().release(key);
+}
+
+pub fn translation(app: &App, entity: Entity) -> Vec2
+{
+    app.world().get::<Transform>(entity).unwrap().translation.xy()
+}
+
+/// Every `E` written since [`collect`] was called.
+#[derive(Resource)]
+pub struct Collected<E: Send + Sync + 'static>(pub Vec<E>);
+
+pub fn collect<E: Event + Clone>(app: &mut App)
+{
+    app.insert_resource(Collected::<E>(Vec::new()))
+        .add_systems(Last, |mut events: EventReader<E>, mut collected: ResMut<Collected<E>>| {
+            collected.0.extend(events.read().cloned());
+        });
+}
+
+pub fn collected<E: Event + Clone>(app: &App) -> &[E]
+{
+    &app.world().resource::<Collected<E>>().0
+}
+
+/// A wide slab of fixed terrain whose top surface is at `y = 0`.
+pub fn spawn_ground(app: &mut App) -> Entity
+{
+    app.world_mut().spawn((
+        Collider::cuboid(500.0, 10.0),
+        Transform::from_xyz(0.0, -10.0, 0.0),
+        RigidBody::Fixed,
+        sticky_bomb::physics::collision_groups(sticky_bomb::physics::GROUP_NORMAL_TERRAIN),
+    )).id()
+}
+
+/// A [`Player`] configured like the one in the default level, carrying a bomb placer.
+pub fn spawn_player(app: &mut App, position: Vec2) -> Entity
+{
+    app.world_mut().spawn((
+        Player,
+        CharacterBundle::with_non_defaults(
+            KinematicCharacterController {
+                slide: true,
+                snap_to_ground: None,
+                apply_impulse_to_dynamic_bodies: true,
+                ..default()
+            },
+            Sprite::default(),
+            Collider::cuboid(6.0, 8.0),
+            Transform::from_translation(position.extend(0.0)),
+        ),
+    ))
+    .with_child(BombPlacerBundle::ball_with_radius(16.0))
+    .id()
+}
+
+/// An [`Enemy`] without a patrol, carrying a bomb place spot. Returns `(enemy, spot)`.
+pub fn spawn_enemy(app: &mut App, position: Vec2) -> (Entity, Entity)
+{
+    let enemy = app.world_mut().spawn((
+        Enemy,
+        EnemyState::default(),
+        CharacterBundle::with_non_defaults(
+            KinematicCharacterController {
+                slide: true,
+                snap_to_ground: None,
+                ..default()
+            },
+            Sprite::default(),
+            Collider::cuboid(6.0, 8.0),
+            Transform::from_translation(position.extend(0.0)),
+        ).with_memberships(GROUP_ENEMY),
+    )).id();
+    let spot = app.world_mut().spawn((BombPlaceSpotBundle::ball_with_radius(24.0), ChildOf(enemy))).id();
+    (enemy, spot)
+}
+
+/// A fixed post with a [`BombPlaceSpot`](sticky_bomb::bomb::BombPlaceSpot) child. Returns `(post, spot)`.
+pub fn spawn_place_spot(app: &mut App, position: Vec2) -> (Entity, Entity)
+{
+    let post = app.world_mut().spawn((
+        Collider::cuboid(4.0, 4.0),
+        Transform::from_translation(position.extend(0.0)),
+        RigidBody::Fixed,
+    )).id();
+    let spot = app.world_mut().spawn((BombPlaceSpotBundle::ball_with_radius(24.0), ChildOf(post))).id();
+    (post, spot)
+}
+
+/// Runs updates until `handle` is loaded, panicking if it takes too long.
+pub fn load<A: Asset>(app: &mut App, handle: &Handle<A>)
+{
+    for _ in 0..1000
+    {
+        if app.world().resource::<AssetServer>().is_loaded_with_dependencies(handle) { return; }
+        app.update();
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    panic!("asset {handle:?} did not load");
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use sticky_bomb::destructible::{TerrainChunk, TerrainMask};
+use sticky_bomb::explosion::Explosion;
+
+use common::*;
+
+fn carve(app: &mut App, position: Vec2, carve_radius: f32)
+{
+    app.world_mut().send_event(Explosion { carve_radius, ..Explosion::new(position, carve_radius, 0.0, 0.0) });
+}
+
+/// When each chunk's collider was last changed.
+fn chunk_colliders(app: &mut App) -> HashMap<UVec2, u32>
+{
+    let mut chunks = app.world_mut().query::<(&TerrainChunk, Ref<Collider>)>();
+    chunks.iter(app.world()).map(|(chunk, collider)| (chunk.0, collider.last_changed().get())).collect()
+}
+
+#[test]
+fn outline_of_a_solid_mask_is_closed()
+{
+    let mask = TerrainMask::solid(40, 20, 4.0);
+    let segments: Vec<[Vec2; 2]> = (0..mask.chunks().y)
+        .flat_map(|y| (0..mask.chunks().x).map(move |x| UVec2::new(x, y)))
+        .flat_map(|chunk| mask.chunk_segments(chunk))
+        .collect();
+
+    assert!(!segments.is_empty());
+    for point in segments.iter().flatten()
+    {
+        let shared = segments.iter().flatten().filter(|other| other.distance(*point) < 1e-4).count();
+        assert_eq!(shared, 2, "{point} ends exactly two segments");
+    }
+    for point in segments.iter().flatten()
+    {
+        assert!(point.x.abs() <= 80.0 && point.y.abs() <= 40.0, "{point} is inside the mask");
+    }
+}
+
+#[test]
+fn carving_empties_cells_within_the_radius()
+{
+    let mut mask = TerrainMask::solid(20, 20, 4.0);
+    assert!(mask.carve_circle(Vec2::ZERO, 8.0));
+
+    let centre = mask.cell_at(Vec2::ZERO);
+    assert!(!mask.is_solid(centre.x, centre.y));
+    assert!(mask.is_solid(0, 0));
+    assert!(!mask.carve_circle(Vec2::ZERO, 8.0), "nothing is left to carve there");
+}
+
+#[test]
+fn explosions_only_rebuild_the_chunks_they_touch()
+{
+    let mut app = headless_app();
+    let terrain = app.world_mut().spawn(TerrainMask::solid(64, 16, 4.0)).id();
+    step(&mut app, 2);
+    let before = chunk_colliders(&mut app);
+    assert_eq!(before.len(), 10, "5 by 2 chunks, each with an outline");
+
+    // Well inside the leftmost chunks.
+    carve(&mut app, Vec2::new(-100.0, 0.0), 10.0);
+    step(&mut app, 2);
+    let after = chunk_colliders(&mut app);
+
+    let mask = app.world().get::<TerrainMask>(terrain).unwrap();
+    let hole = mask.cell_at(Vec2::new(-100.0, 0.0));
+    assert!(!mask.is_solid(hole.x, hole.y));
+    let rebuilt: Vec<UVec2> = before.keys().copied().filter(|chunk| after.get(chunk) != before.get(chunk)).collect();
+    assert!(!rebuilt.is_empty());
+    assert!(rebuilt.iter().all(|chunk| chunk.x <= 1), "only chunks near the hole were rebuilt: {rebuilt:?}");
+}
+
+#[test]
+fn balls_fall_through_a_carved_hole()
+{
+    let mut app = headless_app();
+    app.world_mut().spawn((TerrainMask::solid(40, 4, 4.0), Transform::from_xyz(0.0, 0.0, 0.0)));
+    let ball = app.world_mut().spawn((
+        RigidBody::Dynamic,
+        Collider::ball(3.0),
+        Transform::from_xyz(0.0, 20.0, 0.0),
+    )).id();
+    step(&mut app, 60);
+    assert!(translation(&app, ball).y > 8.0, "the ball rests on the terrain");
+
+    carve(&mut app, Vec2::ZERO, 12.0);
+    step(&mut app, 60);
+    assert!(translation(&app, ball).y < -8.0, "the ball fell through the hole");
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use sticky_bomb::bomb::Bomb;
+use sticky_bomb::enemy::{EnemyState, Patrol};
+use sticky_bomb::physics::{collision_groups, GROUP_NORMAL_TERRAIN};
+
+use common::*;
+
+fn state(app: &App, enemy: Entity) -> EnemyState
+{
+    *app.world().get::<EnemyState>(enemy).unwrap()
+}
+
+#[test]
+fn enemy_patrols_between_its_points()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let (enemy, _) = spawn_enemy(&mut app, Vec2::new(0.0, 10.0));
+    app.world_mut().entity_mut(enemy).insert(Patrol::around(Vec2::ZERO, 30.0));
+
+    let (mut min, mut max) = (f32::MAX, f32::MIN);
+    for _ in 0..600
+    {
+        step(&mut app, 1);
+        let x = translation(&app, enemy).x;
+        (min, max) = (min.min(x), max.max(x));
+    }
+    assert_eq!(state(&app, enemy), EnemyState::Patrol);
+    assert!((min + 30.0).abs() < 8.0 && (max - 30.0).abs() < 8.0, "patrolled between {min} and {max}");
+}
+
+#[test]
+fn enemy_chases_a_player_in_sight()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let (enemy, _) = spawn_enemy(&mut app, Vec2::new(0.0, 10.0));
+    spawn_player(&mut app, Vec2::new(100.0, 10.0));
+    step(&mut app, 60);
+
+    assert_eq!(state(&app, enemy), EnemyState::Chase);
+    assert!(translation(&app, enemy).x > 20.0);
+}
+
+#[test]
+fn walls_block_the_enemy_sight()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    app.world_mut().spawn((
+        Collider::cuboid(4.0, 40.0),
+        Transform::from_xyz(50.0, 40.0, 0.0),
+        RigidBody::Fixed,
+        collision_groups(GROUP_NORMAL_TERRAIN),
+    ));
+    let (enemy, _) = spawn_enemy(&mut app, Vec2::new(0.0, 10.0));
+    spawn_player(&mut app, Vec2::new(100.0, 10.0));
+    step(&mut app, 60);
+
+    assert_eq!(state(&app, enemy), EnemyState::Patrol);
+}
+
+#[test]
+fn enemy_hops_over_low_obstacles_while_chasing()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    app.world_mut().spawn((
+        // Low enough not to hide the player.
+        Collider::cuboid(4.0, 3.0),
+        Transform::from_xyz(40.0, 3.0, 0.0),
+        RigidBody::Fixed,
+        collision_groups(GROUP_NORMAL_TERRAIN),
+    ));
+    let (enemy, _) = spawn_enemy(&mut app, Vec2::new(0.0, 10.0));
+    spawn_player(&mut app, Vec2::new(120.0, 10.0));
+    step(&mut app, 180);
+
+    assert!(translation(&app, enemy).x > 60.0, "got stuck at {}", translation(&app, enemy).x);
+}
+
+#[test]
+fn enemy_flees_with_a_bomb_stuck_to_it()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let (enemy, spot) = spawn_enemy(&mut app, Vec2::new(0.0, 10.0));
+    spawn_player(&mut app, Vec2::new(30.0, 10.0));
+    step(&mut app, 10);
+
+    app.world_mut().spawn((Bomb, Transform::default(), ChildOf(spot)));
+    step(&mut app, 60);
+
+    assert_eq!(state(&app, enemy), EnemyState::Flee);
+    assert!(translation(&app, enemy).x < -20.0, "ran away from the player");
+}
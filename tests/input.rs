@@ -0,0 +1,104 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy::input::gamepad::{Gamepad, GamepadButton};
+
+use sticky_bomb::character::MovementState;
+use sticky_bomb::input::{Action, InputBindings, PendingRebind};
+
+use common::*;
+
+fn actions(app: &App) -> &ButtonInput<Action>
+{
+    app.world().resource::<ButtonInput<Action>>()
+}
+
+#[test]
+fn default_keys_trigger_their_actions()
+{
+    let mut app = headless_app();
+    press(&mut app, KeyCode::ArrowDown);
+    app.update();
+    assert!(actions(&app).just_pressed(Action::PlaceBomb));
+    assert!(!actions(&app).pressed(Action::Jump));
+
+    app.update();
+    assert!(actions(&app).pressed(Action::PlaceBomb));
+    assert!(!actions(&app).just_pressed(Action::PlaceBomb), "only the first update is a fresh press");
+
+    release(&mut app, KeyCode::ArrowDown);
+    app.update();
+    assert!(actions(&app).just_released(Action::PlaceBomb));
+}
+
+#[test]
+fn ball_debug_keys_are_actions_too()
+{
+    let mut app = headless_app();
+    press(&mut app, KeyCode::Space);
+    press(&mut app, KeyCode::KeyA);
+    app.update();
+    assert!(actions(&app).just_pressed(Action::BallJump));
+    assert!(actions(&app).pressed(Action::BallSpinLeft));
+    assert!(!actions(&app).pressed(Action::BallSpinRight));
+}
+
+#[test]
+fn gamepad_buttons_and_stick_trigger_actions()
+{
+    let mut app = headless_app();
+    let pad = app.world_mut().spawn(Gamepad::default()).id();
+
+    app.world_mut().get_mut::<Gamepad>(pad).unwrap().digital_mut().press(GamepadButton::South);
+    app.world_mut().get_mut::<Gamepad>(pad).unwrap().analog_mut().set(GamepadAxis::LeftStickX, -0.8);
+    app.update();
+
+    assert!(actions(&app).just_pressed(Action::Jump));
+    assert!(actions(&app).pressed(Action::MoveLeft));
+    assert!(!actions(&app).pressed(Action::MoveRight));
+}
+
+#[test]
+fn rebinding_captures_the_next_key()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let player = spawn_player(&mut app, Vec2::new(0.0, 10.0));
+    step(&mut app, 30);
+
+    app.world_mut().resource_mut::<PendingRebind>().0 = Some(Action::Jump);
+    press(&mut app, KeyCode::KeyW);
+    step(&mut app, 1);
+    assert!(!app.world().resource::<ButtonInput<Action>>().pressed(Action::MoveLeft));
+    release(&mut app, KeyCode::KeyW);
+    step(&mut app, 1);
+    assert_eq!(app.world().get::<MovementState>(player).unwrap().velocity.y, 0.0, "binding a key doesn't trigger it");
+
+    assert_eq!(app.world().resource::<PendingRebind>().0, None);
+    assert_eq!(app.world().resource::<InputBindings>().keyboard[&Action::Jump], vec![KeyCode::KeyW]);
+
+    press(&mut app, KeyCode::ArrowUp);
+    step(&mut app, 1);
+    assert_eq!(app.world().get::<MovementState>(player).unwrap().velocity.y, 0.0, "the old key no longer jumps");
+    release(&mut app, KeyCode::ArrowUp);
+
+    press(&mut app, KeyCode::KeyW);
+    step(&mut app, 1);
+    assert!(app.world().get::<MovementState>(player).unwrap().velocity.y > 0.0, "the new key jumps");
+}
+
+#[test]
+fn bindings_round_trip_through_a_file()
+{
+    let path = std::env::temp_dir().join(format!("sticky_bomb_bindings_{}.ron", std::process::id()));
+    let mut bindings = InputBindings::default();
+    bindings.bind_key(Action::Detonate, KeyCode::KeyZ);
+    bindings.bind_button(Action::PlaceBomb, GamepadButton::North);
+
+    bindings.save(&path).unwrap();
+    let loaded = InputBindings::load_or_default(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded, bindings);
+    assert_eq!(InputBindings::load_or_default(&path), InputBindings::default(), "a missing file gives the defaults");
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use std::borrow::Cow;
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use sticky_bomb::bomb::{Bomb, BombDetonated, BombKind, Fuse, OwnedBombs};
+use sticky_bomb::inventory::{bomb_pickup, BombInventory, BombPickup};
+use sticky_bomb::throw::BombThrower;
+
+use common::*;
+
+const MINE: BombKind = BombKind(Cow::Borrowed("mine"));
+
+fn spawn_carrier(app: &mut App, inventory: BombInventory) -> Entity
+{
+    let player = spawn_player(app, Vec2::new(0.0, 8.0));
+    app.world_mut().entity_mut(player).insert((inventory, BombThrower::default()));
+    player
+}
+
+fn inventory(app: &App, player: Entity) -> &BombInventory
+{
+    app.world().get::<BombInventory>(player).unwrap()
+}
+
+fn owned(app: &App, player: Entity) -> Vec<Entity>
+{
+    app.world().get::<OwnedBombs>(player).map_or(Vec::new(), |owned| owned.iter().collect())
+}
+
+/// Throws a bomb with a long fuse, so it only goes off when detonated.
+fn throw(app: &mut App, player: Entity)
+{
+    tap(app, KeyCode::ControlRight);
+    for bomb in owned(app, player)
+    {
+        app.world_mut().get_mut::<Fuse>(bomb).unwrap().0.set_duration(Duration::from_secs(60));
+    }
+}
+
+fn tap(app: &mut App, key: KeyCode)
+{
+    press(app, key);
+    step(app, 1);
+    release(app, key);
+    step(app, 1);
+}
+
+#[test]
+fn inventory_is_capped_and_cycles_past_empty_kinds()
+{
+    let mut inventory = BombInventory::new(5).with(BombKind::STANDARD, 1).with(MINE, 0).with(BombKind(Cow::Borrowed("cluster")), 2);
+    assert_eq!(inventory.add(BombKind::STANDARD, 10), 4);
+    assert_eq!(inventory.count(&BombKind::STANDARD), 5);
+
+    inventory.cycle();
+    assert_eq!(inventory.selected().unwrap().kind.0, "cluster", "the empty mines are skipped");
+    assert_eq!(inventory.take_selected().unwrap().0, "cluster");
+    assert_eq!(inventory.take_selected().unwrap().0, "cluster");
+    assert_eq!(inventory.take_selected(), None);
+}
+
+#[test]
+fn throwing_uses_up_the_inventory()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let player = spawn_carrier(&mut app, BombInventory::new(9).with(MINE, 2));
+    step(&mut app, 10);
+
+    for _ in 0..3
+    {
+        throw(&mut app, player);
+    }
+    let bombs: Vec<BombKind> = app.world_mut().query_filtered::<&BombKind, With<Bomb>>().iter(app.world()).cloned().collect();
+    assert_eq!(bombs, vec![MINE, MINE], "the third throw has nothing to throw");
+    assert_eq!(inventory(&app, player).count(&MINE), 0);
+}
+
+#[test]
+fn detonates_the_oldest_bomb_or_all_of_them()
+{
+    let mut app = headless_app();
+    collect::<BombDetonated>(&mut app);
+    spawn_ground(&mut app);
+    let player = spawn_carrier(&mut app, BombInventory::new(9).with(BombKind::STANDARD, 3));
+    step(&mut app, 10);
+
+    for _ in 0..3
+    {
+        throw(&mut app, player);
+    }
+    let thrown = owned(&app, player);
+    assert_eq!(thrown.len(), 3);
+
+    tap(&mut app, KeyCode::Slash);
+    let detonated: Vec<Entity> = collected::<BombDetonated>(&app).iter().map(|d| d.bomb).collect();
+    assert_eq!(detonated, vec![thrown[0]]);
+    assert_eq!(owned(&app, player), thrown[1..]);
+
+    tap(&mut app, KeyCode::ShiftRight);
+    assert_eq!(collected::<BombDetonated>(&app).len(), 3);
+    assert!(owned(&app, player).is_empty());
+}
+
+#[test]
+fn every_player_detonates_on_the_shared_input()
+{
+    let mut app = headless_app();
+    collect::<BombDetonated>(&mut app);
+    spawn_ground(&mut app);
+    let first = spawn_carrier(&mut app, BombInventory::new(9).with(BombKind::STANDARD, 2));
+    let second = spawn_player(&mut app, Vec2::new(-80.0, 8.0));
+    app.world_mut().entity_mut(second).insert((BombInventory::new(9).with(BombKind::STANDARD, 2), BombThrower::default()));
+    step(&mut app, 10);
+
+    for _ in 0..2
+    {
+        throw(&mut app, first);
+    }
+    for bomb in owned(&app, second)
+    {
+        app.world_mut().get_mut::<Fuse>(bomb).unwrap().0.set_duration(Duration::from_secs(60));
+    }
+    let (first_thrown, second_thrown) = (owned(&app, first), owned(&app, second));
+    assert_eq!((first_thrown.len(), second_thrown.len()), (2, 2), "both players throw on the one input");
+
+    tap(&mut app, KeyCode::Slash);
+    let mut detonated: Vec<Entity> = collected::<BombDetonated>(&app).iter().map(|d| d.bomb).collect();
+    detonated.sort();
+    let mut oldest = vec![first_thrown[0], second_thrown[0]];
+    oldest.sort();
+    assert_eq!(detonated, oldest, "each player's oldest bomb goes off");
+
+    tap(&mut app, KeyCode::ShiftRight);
+    assert_eq!(collected::<BombDetonated>(&app).len(), 4);
+    assert!(owned(&app, first).is_empty() && owned(&app, second).is_empty());
+}
+
+#[test]
+fn pickups_refill_up_to_capacity()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let player = spawn_carrier(&mut app, BombInventory::new(4).with(BombKind::STANDARD, 2));
+    let full = app.world_mut().spawn(bomb_pickup(BombKind::STANDARD, 3, Vec2::new(0.0, 8.0))).id();
+    let mines = app.world_mut().spawn(bomb_pickup(MINE, 1, Vec2::new(0.0, 8.0))).id();
+    step(&mut app, 5);
+
+    assert_eq!(inventory(&app, player).count(&BombKind::STANDARD), 4);
+    assert_eq!(inventory(&app, player).count(&MINE), 1);
+    assert_eq!(app.world().get::<BombPickup>(full).map(|pickup| pickup.count), Some(1), "what didn't fit is left behind");
+    assert!(app.world().get_entity(mines).is_err());
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use bevy::prelude::*;
+
+use sticky_bomb::bomb::{armed_bomb, thrown_bomb, Bomb, BombDetonated, BombKind, BombPlaceSpot};
+use sticky_bomb::bomb_type::StandardBomb;
+use sticky_bomb::chain_reaction::ChainReactions;
+use sticky_bomb::character::{Enemy, Player};
+use sticky_bomb::destructible::TerrainMask;
+use sticky_bomb::inventory::{BombInventory, BombPickup};
+use sticky_bomb::level::{Ball, CurrentLevel, Level, LevelEntity, PlayerSpawn, Terrain, TerrainShape, DEFAULT_LEVEL};
+
+use common::*;
+
+fn count<C: Component>(app: &mut App) -> usize
+{
+    app.world_mut().query_filtered::<(), With<C>>().iter(app.world()).count()
+}
+
+fn player_at(position: Vec2) -> PlayerSpawn
+{
+    ron::from_str(&format!("(position: ({:?}, {:?}))", position.x, position.y)).unwrap()
+}
+
+#[test]
+fn default_level_parses()
+{
+    let level: Level = ron::from_str(include_str!("../assets/levels/default.level.ron")).unwrap();
+    assert_eq!(level.terrain.len(), 2);
+    assert_eq!(level.players, vec![player_at(Vec2::new(120.0, 60.0))]);
+    assert_eq!(level.players[0].movement, "movement/player.movement.ron");
+    assert_eq!(level.balls[0].place_spot, Some(20.0));
+}
+
+#[test]
+fn current_level_is_spawned_once_loaded()
+{
+    let mut app = headless_app();
+    let handle = app.world().resource::<AssetServer>().load(DEFAULT_LEVEL);
+    app.insert_resource(CurrentLevel(handle.clone()));
+    load(&mut app, &handle);
+    step(&mut app, 1);
+
+    assert_eq!(count::<Player>(&mut app), 1);
+    assert_eq!(count::<Enemy>(&mut app), 1);
+    assert_eq!(count::<Ball>(&mut app), 1);
+    assert_eq!(count::<BombPlaceSpot>(&mut app), 2);
+    assert_eq!(count::<BombPickup>(&mut app), 1);
+    assert_eq!(count::<BombInventory>(&mut app), 1, "players carry their bombs");
+    assert_eq!(count::<TerrainMask>(&mut app), 1);
+    assert_eq!(count::<LevelEntity>(&mut app), 7);
+}
+
+#[test]
+fn players_land_on_level_terrain()
+{
+    let mut app = headless_app();
+    let level = Level {
+        terrain: vec![Terrain {
+            position: Vec2::new(0.0, -10.0),
+            shape: TerrainShape::Rect { half_extents: Vec2::new(100.0, 10.0) },
+            friction: 0.5,
+            restitution: 0.5,
+        }],
+        players: vec![player_at(Vec2::new(0.0, 40.0))],
+        ..default()
+    };
+    let handle = app.world_mut().resource_mut::<Assets<Level>>().add(level);
+    app.insert_resource(CurrentLevel(handle));
+    step(&mut app, 120);
+
+    let player = app.world_mut().query_filtered::<Entity, With<Player>>().single(app.world()).unwrap();
+    assert!((translation(&app, player).y - 8.0).abs() < 1.0);
+}
+
+#[test]
+fn editing_the_level_respawns_it()
+{
+    let mut app = headless_app();
+    let level = Level { players: vec![player_at(Vec2::ZERO)], ..default() };
+    let handle = app.world_mut().resource_mut::<Assets<Level>>().add(level);
+    app.insert_resource(CurrentLevel(handle.clone()));
+    step(&mut app, 1);
+    assert_eq!(count::<Player>(&mut app), 1);
+
+    app.world_mut().spawn(thrown_bomb(Vec2::new(0.0, 200.0), Vec2::ZERO));
+    app.world_mut().spawn((armed_bomb(BombKind::STANDARD, &StandardBomb::default()), Transform::from_xyz(-100.0, 0.0, 0.0)));
+    step(&mut app, 1);
+    app.world_mut().send_event(BombDetonated { bomb: Entity::PLACEHOLDER, kind: BombKind::STANDARD, position: Vec2::new(-110.0, 0.0) });
+    step(&mut app, 2);
+    assert_eq!(app.world().resource::<ChainReactions>().len(), 1);
+
+    app.world_mut().resource_mut::<Assets<Level>>().get_mut(&handle).unwrap().players.push(player_at(Vec2::new(50.0, 0.0)));
+    step(&mut app, 2);
+    assert_eq!(count::<Player>(&mut app), 2, "the old players are replaced, not kept");
+    assert_eq!(count::<Bomb>(&mut app), 0, "bombs from the old level are gone");
+    assert!(app.world().resource::<ChainReactions>().is_empty());
+}
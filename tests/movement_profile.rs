@@ -0,0 +1,80 @@
+mod common;
+
+use bevy::prelude::*;
+
+use sticky_bomb::movement::{MovementProfile, MovementProfileHandle};
+
+use common::*;
+
+#[test]
+fn shipped_player_profile_matches_the_defaults()
+{
+    let profile: MovementProfile = ron::from_str(include_str!("../assets/movement/player.movement.ron")).unwrap();
+    assert_eq!(profile, MovementProfile::default());
+}
+
+#[test]
+fn missing_fields_keep_their_defaults()
+{
+    let profile: MovementProfile = ron::from_str("(max_speed: 80.0)").unwrap();
+    assert_eq!(profile.max_speed, 80.0);
+    assert_eq!(profile.acceleration, MovementProfile::default().acceleration);
+}
+
+#[test]
+fn profile_files_load_through_the_asset_server()
+{
+    let mut app = headless_app();
+    let handle: Handle<MovementProfile> = app.world().resource::<AssetServer>().load("movement/player.movement.ron");
+    load(&mut app, &handle);
+
+    let assets = app.world().resource::<Assets<MovementProfile>>();
+    assert_eq!(assets.get(&handle), Some(&MovementProfile::default()));
+}
+
+#[test]
+fn character_moves_with_its_profile()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let slow = MovementProfile { max_speed: 80.0, ..default() };
+    let handle = app.world_mut().resource_mut::<Assets<MovementProfile>>().add(slow);
+    let player = spawn_player(&mut app, Vec2::new(0.0, 10.0));
+    app.world_mut().entity_mut(player).insert(MovementProfileHandle(handle.clone()));
+    step(&mut app, 30);
+
+    press(&mut app, KeyCode::ArrowRight);
+    step(&mut app, 60);
+    let before = translation(&app, player);
+    step(&mut app, 60);
+    let travelled = translation(&app, player).x - before.x;
+    assert!((travelled - 80.0).abs() < 2.0, "one second at the profile's max speed, got {travelled}");
+
+    // Editing the asset, as a hot reload would, takes effect immediately.
+    app.world_mut().resource_mut::<Assets<MovementProfile>>().get_mut(&handle).unwrap().max_speed = 40.0;
+    step(&mut app, 60);
+    let before = translation(&app, player);
+    step(&mut app, 60);
+    let travelled = translation(&app, player).x - before.x;
+    assert!((travelled - 40.0).abs() < 2.0, "one second at the edited max speed, got {travelled}");
+}
+
+#[test]
+fn jump_reaches_the_profile_jump_height()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let player = spawn_player(&mut app, Vec2::new(0.0, 10.0));
+    step(&mut app, 30);
+    let ground_y = translation(&app, player).y;
+
+    press(&mut app, KeyCode::ArrowUp);
+    let mut peak = ground_y;
+    for _ in 0..90
+    {
+        step(&mut app, 1);
+        peak = peak.max(translation(&app, player).y);
+    }
+    let height = peak - ground_y;
+    assert!((height - MovementProfile::default().jump_height).abs() < 2.0, "jumped {height}px");
+}
@@ -0,0 +1,219 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use sticky_bomb::character::{Knockback, MovementState};
+
+use common::*;
+
+fn grounded(app: &App, player: Entity) -> bool
+{
+    app.world().get::<KinematicCharacterControllerOutput>(player).is_some_and(|output| output.grounded)
+}
+
+#[test]
+fn player_falls_and_lands_on_ground()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let player = spawn_player(&mut app, Vec2::new(0.0, 40.0));
+
+    step(&mut app, 120);
+
+    assert!(grounded(&app, player));
+    let resting = translation(&app, player);
+    assert!((resting.y - 8.0).abs() < 1.0, "player rests on the ground, got {resting}");
+
+    step(&mut app, 30);
+    assert_eq!(translation(&app, player).y, resting.y);
+}
+
+#[test]
+fn holding_right_accelerates_up_to_max_speed()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let player = spawn_player(&mut app, Vec2::new(0.0, 10.0));
+    step(&mut app, 30);
+
+    press(&mut app, KeyCode::ArrowRight);
+    step(&mut app, 120);
+
+    let before = translation(&app, player);
+    step(&mut app, 60);
+    let travelled = translation(&app, player).x - before.x;
+    assert!((travelled - 160.0).abs() < 2.0, "one second at max speed, got {travelled}");
+    assert!(!app.world().get::<Sprite>(player).unwrap().flip_x);
+
+    release(&mut app, KeyCode::ArrowRight);
+    step(&mut app, 30);
+    let stopped = translation(&app, player);
+    step(&mut app, 10);
+    assert_eq!(translation(&app, player).x, stopped.x, "player decelerates to a stop");
+}
+
+#[test]
+fn jump_leaves_the_ground_and_comes_back()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let player = spawn_player(&mut app, Vec2::new(0.0, 10.0));
+    step(&mut app, 30);
+    let ground_y = translation(&app, player).y;
+
+    press(&mut app, KeyCode::ArrowUp);
+    step(&mut app, 20);
+    assert!(translation(&app, player).y > ground_y + 20.0);
+    assert!(!grounded(&app, player));
+
+    release(&mut app, KeyCode::ArrowUp);
+    step(&mut app, 120);
+    assert!(grounded(&app, player));
+    assert!((translation(&app, player).y - ground_y).abs() < 1.0);
+}
+
+#[test]
+fn each_player_keeps_its_own_velocity()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let pushed = spawn_player(&mut app, Vec2::new(-100.0, 10.0));
+    let idle = spawn_player(&mut app, Vec2::new(100.0, 10.0));
+    step(&mut app, 30);
+    let idle_start = translation(&app, idle);
+
+    app.world_mut().get_mut::<Knockback>(pushed).unwrap().0 = Vec2::new(100.0, 0.0);
+    step(&mut app, 5);
+
+    assert!(translation(&app, pushed).x > -100.0);
+    assert_eq!(translation(&app, idle), idle_start);
+    assert_eq!(app.world().get::<MovementState>(idle).unwrap().velocity, Vec2::ZERO);
+}
+
+fn vertical_velocity(app: &App, player: Entity) -> f32
+{
+    app.world().get::<MovementState>(player).unwrap().velocity.y
+}
+
+/// Highest point reached over `ticks` updates.
+fn peak(app: &mut App, player: Entity, ticks: usize) -> f32
+{
+    let mut peak = translation(app, player).y;
+    for _ in 0..ticks
+    {
+        step(app, 1);
+        peak = peak.max(translation(app, player).y);
+    }
+    peak
+}
+
+/// Walks a player off the right edge of a narrow ledge and returns it on the first airborne tick.
+fn walk_off_ledge(app: &mut App) -> Entity
+{
+    app.world_mut().spawn((
+        Collider::cuboid(20.0, 10.0),
+        Transform::from_xyz(0.0, -10.0, 0.0),
+        RigidBody::Fixed,
+    ));
+    let player = spawn_player(app, Vec2::new(0.0, 10.0));
+    step(app, 30);
+    assert!(grounded(app, player));
+
+    press(app, KeyCode::ArrowRight);
+    for _ in 0..120
+    {
+        step(app, 1);
+        if !grounded(app, player) { return player; }
+    }
+    panic!("player never left the ledge");
+}
+
+#[test]
+fn jump_is_allowed_shortly_after_leaving_a_ledge()
+{
+    let mut app = headless_app();
+    let player = walk_off_ledge(&mut app);
+    step(&mut app, 2);
+
+    press(&mut app, KeyCode::ArrowUp);
+    step(&mut app, 1);
+    assert!(vertical_velocity(&app, player) > 0.0, "coyote jump fires");
+}
+
+#[test]
+fn jump_is_refused_once_coyote_time_is_over()
+{
+    let mut app = headless_app();
+    let player = walk_off_ledge(&mut app);
+    step(&mut app, 15);
+
+    press(&mut app, KeyCode::ArrowUp);
+    step(&mut app, 1);
+    assert!(vertical_velocity(&app, player) < 0.0, "no jump long after leaving the ledge");
+}
+
+#[test]
+fn jump_pressed_just_before_landing_fires_on_landing()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let player = spawn_player(&mut app, Vec2::new(0.0, 40.0));
+    while translation(&app, player).y > 12.0 { step(&mut app, 1); }
+    assert!(!grounded(&app, player));
+
+    press(&mut app, KeyCode::ArrowUp);
+    let mut jumped = false;
+    for _ in 0..7
+    {
+        step(&mut app, 1);
+        jumped |= vertical_velocity(&app, player) > 0.0;
+    }
+    assert!(jumped, "buffered jump fires once the player lands");
+}
+
+#[test]
+fn jump_pressed_long_before_landing_is_forgotten()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let player = spawn_player(&mut app, Vec2::new(0.0, 100.0));
+    step(&mut app, 1);
+
+    press(&mut app, KeyCode::ArrowUp);
+    step(&mut app, 120);
+    assert!(grounded(&app, player));
+    assert_eq!(vertical_velocity(&app, player), 0.0);
+}
+
+#[test]
+fn releasing_jump_early_gives_a_short_hop()
+{
+    let mut full = headless_app();
+    spawn_ground(&mut full);
+    let player = spawn_player(&mut full, Vec2::new(0.0, 10.0));
+    step(&mut full, 30);
+    let ground_y = translation(&full, player).y;
+    press(&mut full, KeyCode::ArrowUp);
+    let full_height = peak(&mut full, player, 90) - ground_y;
+
+    let mut short = headless_app();
+    spawn_ground(&mut short);
+    let player = spawn_player(&mut short, Vec2::new(0.0, 10.0));
+    step(&mut short, 30);
+    press(&mut short, KeyCode::ArrowUp);
+    step(&mut short, 5);
+    release(&mut short, KeyCode::ArrowUp);
+    let short_height = peak(&mut short, player, 90) - ground_y;
+
+    assert!(short_height < full_height * 0.5, "short hop {short_height}px vs full jump {full_height}px");
+}
+
+#[test]
+fn falling_speed_is_capped()
+{
+    let mut app = headless_app();
+    let player = spawn_player(&mut app, Vec2::new(0.0, 0.0));
+    step(&mut app, 240);
+    assert_eq!(vertical_velocity(&app, player), -320.0);
+}
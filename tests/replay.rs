@@ -0,0 +1,85 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use sticky_bomb::input::Action;
+use sticky_bomb::replay::{InputRecorder, InputRecording, InputReplay};
+
+use common::*;
+
+/// A player, a bomb spot and a ball to blow around.
+fn spawn_scene(app: &mut App) -> Vec<Entity>
+{
+    spawn_ground(app);
+    let player = spawn_player(app, Vec2::new(0.0, 10.0));
+    let (post, _) = spawn_place_spot(app, Vec2::new(40.0, 4.0));
+    let ball = app.world_mut().spawn((
+        RigidBody::Dynamic,
+        Collider::ball(6.0),
+        Transform::from_xyz(60.0, 6.0, 0.0),
+    )).id();
+    vec![player, post, ball]
+}
+
+/// Transforms of `entities` after each tick.
+fn trace(app: &mut App, entities: &[Entity], ticks: usize) -> Vec<Vec<Transform>>
+{
+    (0..ticks).map(|_| {
+        step(app, 1);
+        entities.iter().map(|&e| *app.world().get::<Transform>(e).unwrap()).collect()
+    }).collect()
+}
+
+#[test]
+fn recording_merges_repeated_ticks()
+{
+    let mut recording = InputRecording::default();
+    recording.push(vec![]);
+    recording.push(vec![]);
+    recording.push(vec![Action::Jump]);
+    recording.push(vec![]);
+
+    assert_eq!(recording.runs, vec![(2, vec![]), (1, vec![Action::Jump]), (1, vec![])]);
+    assert_eq!(recording.len(), 4);
+    assert_eq!(recording.ticks().nth(2), Some(&[Action::Jump][..]));
+}
+
+#[test]
+fn replay_reproduces_the_recorded_run()
+{
+    let mut live = headless_app();
+    live.insert_resource(InputRecorder::default());
+    let entities = spawn_scene(&mut live);
+
+    let mut expected = trace(&mut live, &entities, 20);
+    press(&mut live, KeyCode::ArrowRight);
+    expected.extend(trace(&mut live, &entities, 25));
+    release(&mut live, KeyCode::ArrowRight);
+    press(&mut live, KeyCode::ArrowDown);
+    expected.extend(trace(&mut live, &entities, 3));
+    release(&mut live, KeyCode::ArrowDown);
+    press(&mut live, KeyCode::ArrowUp);
+    press(&mut live, KeyCode::ArrowLeft);
+    expected.extend(trace(&mut live, &entities, 12));
+    release(&mut live, KeyCode::ArrowUp);
+    expected.extend(trace(&mut live, &entities, 220));
+
+    let recording = live.world().resource::<InputRecorder>().recording.clone();
+    assert_eq!(recording.len() as usize, expected.len());
+    let text = ron::to_string(&recording).unwrap();
+
+    let mut replayed = headless_app();
+    replayed.insert_resource(InputReplay::new(ron::from_str(&text).unwrap()));
+    let entities = spawn_scene(&mut replayed);
+    // Keys pressed during a replay are ignored.
+    press(&mut replayed, KeyCode::ArrowLeft);
+    let actual = trace(&mut replayed, &entities, expected.len());
+
+    for (tick, (expected, actual)) in expected.iter().zip(&actual).enumerate()
+    {
+        assert_eq!(expected, actual, "transforms diverged on tick {tick}");
+    }
+    assert!(replayed.world().resource::<InputReplay>().finished());
+    assert!(expected.last().unwrap()[2].translation.x != 60.0, "the bomb moved the ball");
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use sticky_bomb::bomb::{thrown_bomb, Fuse};
+use sticky_bomb::destructible::TerrainMask;
+use sticky_bomb::enemy::EnemyState;
+use sticky_bomb::physics::{collision_groups, GROUP_PROP};
+use sticky_bomb::sticky::{StuckBodies, StuckTo};
+
+use common::*;
+
+fn throw(app: &mut App, position: Vec2, velocity: Vec2) -> Entity
+{
+    let bomb = app.world_mut().spawn(thrown_bomb(position, velocity)).id();
+    // Long enough to outlast the test.
+    app.world_mut().get_mut::<Fuse>(bomb).unwrap().0.set_duration(std::time::Duration::from_secs(60));
+    bomb
+}
+
+fn spawn_ball(app: &mut App, position: Vec2) -> Entity
+{
+    app.world_mut().spawn((
+        RigidBody::Dynamic,
+        Collider::ball(12.0),
+        collision_groups(GROUP_PROP),
+        Velocity::default(),
+        Transform::from_translation(position.extend(0.0)),
+    )).id()
+}
+
+fn host(app: &App, bomb: Entity) -> Option<Entity>
+{
+    app.world().get::<StuckTo>(bomb).map(|stuck| stuck.host)
+}
+
+/// The bomb's pose in its host's space.
+fn relative_pose(app: &App, bomb: Entity, host: Entity) -> (Vec3, Quat)
+{
+    let bomb = app.world().get::<Transform>(bomb).unwrap();
+    let host = app.world().get::<Transform>(host).unwrap();
+    (host.rotation.inverse() * (bomb.translation - host.translation), host.rotation.inverse() * bomb.rotation)
+}
+
+#[test]
+fn bomb_sticks_to_the_ground_where_it_lands()
+{
+    let mut app = headless_app();
+    let ground = spawn_ground(&mut app);
+    let bomb = throw(&mut app, Vec2::new(20.0, 30.0), Vec2::new(40.0, 0.0));
+    step(&mut app, 90);
+
+    assert_eq!(host(&app, bomb), Some(ground));
+    let landed = translation(&app, bomb);
+    assert!(landed.x > 20.0 && landed.y < 8.0, "landed at {landed}");
+
+    step(&mut app, 30);
+    assert_eq!(translation(&app, bomb), landed, "stuck bombs don't slide");
+}
+
+#[test]
+fn bomb_sticks_to_offset_destructible_terrain()
+{
+    let mut app = headless_app();
+    // Only the parent is offset, so the terrain's own `Transform` says nothing about where it is.
+    let parent = app.world_mut().spawn((Transform::from_xyz(100.0, -50.0, 0.0), Visibility::default())).id();
+    let terrain = app.world_mut().spawn((TerrainMask::solid(40, 4, 4.0), ChildOf(parent))).id();
+    step(&mut app, 2);
+    let bomb = throw(&mut app, Vec2::new(110.0, -10.0), Vec2::new(0.0, -60.0));
+    step(&mut app, 60);
+
+    let host = host(&app, bomb).expect("stuck to the terrain");
+    assert!(host == terrain || app.world().get::<ChildOf>(host).is_some_and(|child_of| child_of.parent() == terrain));
+    let landed = translation(&app, bomb);
+    assert!((landed.x - 110.0).abs() < 1.0 && (landed.y + 38.0).abs() < 1.0, "landed on top of the terrain, at {landed}");
+
+    step(&mut app, 30);
+    assert_eq!(translation(&app, bomb), landed, "stays where it landed");
+
+    app.world_mut().get_mut::<Transform>(parent).unwrap().translation.x += 30.0;
+    step(&mut app, 2);
+    let moved = translation(&app, bomb);
+    assert!(moved.distance(landed + Vec2::new(30.0, 0.0)) < 0.01, "moved along with the terrain, to {moved}");
+}
+
+#[test]
+fn bomb_rides_along_on_a_rolling_ball()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let ball = spawn_ball(&mut app, Vec2::new(0.0, 12.0));
+    step(&mut app, 10);
+    let bomb = throw(&mut app, Vec2::new(0.0, 60.0), Vec2::ZERO);
+    for _ in 0..60
+    {
+        step(&mut app, 1);
+        if host(&app, bomb).is_some() { break; }
+    }
+    assert_eq!(host(&app, bomb), Some(ball));
+    assert_eq!(app.world().get::<StuckBodies>(ball).unwrap().iter().collect::<Vec<_>>(), vec![bomb]);
+    let (offset, rotation) = relative_pose(&app, bomb, ball);
+    assert!((offset.length() - 16.0).abs() < 2.0, "stuck on the surface, {offset} from the centre");
+
+    app.world_mut().get_mut::<Velocity>(ball).unwrap().linvel = Vec2::new(60.0, 0.0);
+    app.world_mut().get_mut::<Velocity>(ball).unwrap().angvel = -5.0;
+    step(&mut app, 30);
+
+    assert!(translation(&app, ball).x > 10.0);
+    assert_ne!(app.world().get::<Transform>(ball).unwrap().rotation, Quat::IDENTITY);
+    let (moved_offset, moved_rotation) = relative_pose(&app, bomb, ball);
+    assert!(moved_offset.distance(offset) < 0.01, "kept its place on the ball");
+    assert!(moved_rotation.angle_between(rotation) < 0.01, "kept its orientation on the ball");
+}
+
+#[test]
+fn bomb_falls_off_a_despawned_host_with_its_velocity()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let ball = spawn_ball(&mut app, Vec2::new(0.0, 100.0));
+    app.world_mut().entity_mut(ball).insert(GravityScale(0.0));
+    let bomb = throw(&mut app, Vec2::new(0.0, 140.0), Vec2::new(0.0, -60.0));
+    step(&mut app, 60);
+    assert_eq!(host(&app, bomb), Some(ball));
+
+    app.world_mut().get_mut::<Velocity>(ball).unwrap().linvel = Vec2::new(80.0, 0.0);
+    step(&mut app, 5);
+    app.world_mut().entity_mut(ball).despawn();
+    step(&mut app, 2);
+
+    assert_eq!(host(&app, bomb), None);
+    assert!(app.world().get::<RigidBodyDisabled>(bomb).is_none());
+    let velocity = app.world().get::<Velocity>(bomb).unwrap().linvel;
+    assert!(velocity.x > 60.0, "inherited the host's motion, got {velocity}");
+
+    step(&mut app, 120);
+    assert!(translation(&app, bomb).y < 10.0, "fell to the ground");
+}
+
+#[test]
+fn enemy_flees_from_a_thrown_bomb()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let (enemy, _) = spawn_enemy(&mut app, Vec2::new(0.0, 10.0));
+    spawn_player(&mut app, Vec2::new(60.0, 10.0));
+    step(&mut app, 2);
+    let bomb = throw(&mut app, Vec2::new(40.0, 14.0), Vec2::new(-120.0, 0.0));
+    step(&mut app, 30);
+
+    assert_eq!(host(&app, bomb), Some(enemy));
+    assert_eq!(*app.world().get::<EnemyState>(enemy).unwrap(), EnemyState::Flee);
+}
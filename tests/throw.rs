@@ -0,0 +1,103 @@
+mod common;
+
+use bevy::prelude::*;
+
+use sticky_bomb::bomb::{Bomb, Fuse};
+use sticky_bomb::sticky::StuckTo;
+use sticky_bomb::throw::{BombThrower, ThrowPreview, MAX_AIM};
+
+use common::*;
+
+fn spawn_thrower(app: &mut App, position: Vec2) -> Entity
+{
+    let player = spawn_player(app, position);
+    app.world_mut().entity_mut(player).insert(BombThrower::default());
+    player
+}
+
+fn preview(app: &App, player: Entity) -> &ThrowPreview
+{
+    app.world().get::<ThrowPreview>(player).unwrap()
+}
+
+fn bombs(app: &mut App) -> Vec<Entity>
+{
+    app.world_mut().query_filtered::<Entity, With<Bomb>>().iter(app.world()).collect()
+}
+
+/// Holds the throw for `ticks`, returning the landing point previewed just before letting go.
+fn charge(app: &mut App, player: Entity, ticks: usize) -> Vec2
+{
+    press(app, KeyCode::ControlRight);
+    step(app, ticks);
+    let landing = preview(app, player).landing.expect("the throw lands somewhere");
+    release(app, KeyCode::ControlRight);
+    step(app, 1);
+    landing
+}
+
+#[test]
+fn preview_only_shows_while_charging()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let player = spawn_thrower(&mut app, Vec2::new(0.0, 8.0));
+    step(&mut app, 10);
+    assert_eq!(preview(&app, player), &ThrowPreview::default());
+
+    press(&mut app, KeyCode::ControlRight);
+    step(&mut app, 10);
+    let shown = preview(&app, player);
+    assert!(shown.points.len() > 2);
+    assert!(shown.points.windows(2).all(|pair| pair[1].x > pair[0].x), "thrown the way the player faces");
+
+    release(&mut app, KeyCode::ControlRight);
+    step(&mut app, 1);
+    assert_eq!(preview(&app, player), &ThrowPreview::default());
+}
+
+#[test]
+fn bomb_sticks_where_the_preview_lands()
+{
+    let mut app = headless_app();
+    let ground = spawn_ground(&mut app);
+    let player = spawn_thrower(&mut app, Vec2::new(0.0, 8.0));
+    step(&mut app, 10);
+
+    let landing = charge(&mut app, player, 30);
+    let bomb = bombs(&mut app)[0];
+    app.world_mut().get_mut::<Fuse>(bomb).unwrap().0.set_duration(std::time::Duration::from_secs(60));
+
+    for _ in 0..600
+    {
+        if app.world().get::<StuckTo>(bomb).is_some() { break; }
+        step(&mut app, 1);
+    }
+    assert_eq!(app.world().get::<StuckTo>(bomb).map(|stuck| stuck.host), Some(ground));
+    let stuck_at = translation(&app, bomb);
+    assert!(stuck_at.distance(landing) < 3.0, "previewed {landing:?}, stuck at {stuck_at:?}");
+}
+
+#[test]
+fn charging_longer_throws_further()
+{
+    let mut app = headless_app();
+    spawn_ground(&mut app);
+    let player = spawn_thrower(&mut app, Vec2::new(0.0, 8.0));
+    step(&mut app, 10);
+
+    let short = charge(&mut app, player, 5);
+    let long = charge(&mut app, player, 60);
+    assert!(long.x > short.x + 50.0, "short {short:?}, long {long:?}");
+    assert_eq!(bombs(&mut app).len(), 2);
+}
+
+#[test]
+fn aim_is_clamped()
+{
+    let mut app = headless_app();
+    let player = spawn_thrower(&mut app, Vec2::ZERO);
+    press(&mut app, KeyCode::PageUp);
+    step(&mut app, 120);
+    assert_eq!(app.world().get::<BombThrower>(player).unwrap().aim, MAX_AIM);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use bevy::prelude::*;
+
+use sticky_bomb::character::Player;
+use sticky_bomb::level::{CurrentLevel, Level, PlaceSpotSpawn, Terrain, TerrainShape};
+use sticky_bomb::tiled::{level_from_tiled, merge_tiles};
+
+use common::*;
+
+const MAP: &str = r#"{
+    "width": 6, "height": 4, "tilewidth": 16, "tileheight": 16,
+    "layers": [
+        { "type": "tilelayer", "name": "terrain", "width": 6, "height": 4, "data": [
+            0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 3, 3,
+            0, 0, 0, 0, 3, 3,
+            1, 2, 1, 2, 1, 1
+        ] },
+        { "type": "tilelayer", "name": "background", "width": 6, "height": 4,
+          "properties": [{ "name": "collision", "type": "bool", "value": false }],
+          "data": [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1] },
+        { "type": "group", "name": "spawns", "layers": [
+            { "type": "objectgroup", "name": "things", "objects": [
+                { "id": 1, "name": "hero", "class": "Player", "x": 16, "y": 40, "width": 0, "height": 0, "point": true,
+                  "properties": [{ "name": "movement", "type": "string", "value": "movement/fast.movement.ron" }] },
+                { "id": 2, "name": "grunt", "type": "Enemy", "x": 0, "y": 0, "width": 16, "height": 16,
+                  "properties": [{ "name": "place_spot", "type": "float", "value": 24 }] },
+                { "id": 3, "name": "spot", "class": "BombPlaceSpot", "x": 20, "y": 0, "width": 40, "height": 40, "ellipse": true },
+                { "id": 4, "name": "bush", "class": "Decoration", "x": 0, "y": 0 }
+            ] }
+        ] }
+    ]
+}"#;
+
+#[test]
+fn tiles_merge_into_few_rectangles()
+{
+    let solid: Vec<bool> = (0..64 * 8).map(|i| i / 64 >= 6).collect();
+    let rects = merge_tiles(&solid, 64, 8);
+    assert_eq!(rects, vec![URect::new(0, 6, 64, 8)]);
+
+    // A staircase needs one rectangle per step, and every solid tile is covered exactly once.
+    let stairs: Vec<bool> = (0..16).map(|i| i % 4 <= i / 4).collect();
+    let rects = merge_tiles(&stairs, 4, 4);
+    assert_eq!(rects.len(), 4);
+    assert_eq!(rects.iter().map(|r| r.width() * r.height()).sum::<u32>(), stairs.iter().filter(|&&s| s).count() as u32);
+}
+
+#[test]
+fn tiled_map_becomes_a_level()
+{
+    let level = level_from_tiled(MAP.as_bytes()).unwrap();
+
+    assert_eq!(level.terrain, vec![
+        Terrain { position: Vec2::new(32.0, -8.0), shape: TerrainShape::Rect { half_extents: Vec2::new(16.0, 24.0) }, friction: 0.5, restitution: 0.5 },
+        Terrain { position: Vec2::new(-16.0, -24.0), shape: TerrainShape::Rect { half_extents: Vec2::new(32.0, 8.0) }, friction: 0.5, restitution: 0.5 },
+    ]);
+    assert_eq!(level.players.len(), 1);
+    assert_eq!(level.players[0].position, Vec2::new(-32.0, -8.0));
+    assert_eq!(level.players[0].movement, "movement/fast.movement.ron");
+    assert_eq!(level.players[0].placer_radius, 16.0, "unset properties keep their defaults");
+    assert_eq!(level.enemies.len(), 1);
+    assert_eq!(level.enemies[0].position, Vec2::new(-40.0, 24.0));
+    assert_eq!(level.enemies[0].place_spot, Some(24.0));
+    assert_eq!(level.place_spots, vec![PlaceSpotSpawn { position: Vec2::new(-8.0, 12.0), radius: 20.0 }]);
+    assert!(level.balls.is_empty());
+}
+
+#[test]
+fn bad_object_properties_are_reported()
+{
+    let map = MAP.replace(r#""value": 24"#, r#""value": "big""#);
+    let error = level_from_tiled(map.as_bytes()).unwrap_err().to_string();
+    assert!(error.contains("grunt"), "{error}");
+}
+
+#[test]
+fn truncated_tile_layers_are_reported()
+{
+    let map = MAP.replace("1, 2, 1, 2, 1, 1", "1, 2, 1");
+    let error = level_from_tiled(map.as_bytes()).unwrap_err().to_string();
+    assert!(error.contains("terrain"), "{error}");
+}
+
+#[test]
+fn tiled_maps_load_as_levels()
+{
+    let mut app = headless_app();
+    let handle: Handle<Level> = app.world().resource::<AssetServer>().load("levels/arena.tmj");
+    app.insert_resource(CurrentLevel(handle.clone()));
+    load(&mut app, &handle);
+    step(&mut app, 120);
+
+    let level = app.world().resource::<Assets<Level>>().get(&handle).unwrap();
+    assert!(!level.terrain.is_empty());
+    let player = app.world_mut().query_filtered::<Entity, With<Player>>().single(app.world()).unwrap();
+    let output = app.world().get::<bevy_rapier2d::prelude::KinematicCharacterControllerOutput>(player);
+    assert!(output.is_some_and(|output| output.grounded), "the player lands on the imported terrain");
+}